@@ -0,0 +1,22 @@
+-- AlterTable
+ALTER TABLE `User` ADD COLUMN `firstName` VARCHAR(191) NULL,
+    ADD COLUMN `lastName` VARCHAR(191) NULL;
+
+-- CreateTable
+CREATE TABLE `WorkLog` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `workerId` INTEGER NOT NULL,
+    `employer` VARCHAR(191) NOT NULL,
+    `jobType` VARCHAR(191) NOT NULL,
+    `startTime` DATETIME(3) NOT NULL,
+    `endTime` DATETIME(3) NULL,
+    `breakMinutes` INTEGER NOT NULL DEFAULT 0,
+    `notes` TEXT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `WorkLog` ADD CONSTRAINT `WorkLog_workerId_fkey` FOREIGN KEY (`workerId`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
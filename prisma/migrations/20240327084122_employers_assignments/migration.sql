@@ -0,0 +1,52 @@
+-- CreateTable
+CREATE TABLE `Employer` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `name` VARCHAR(191) NOT NULL,
+    `address` VARCHAR(191) NULL,
+    `contactName` VARCHAR(191) NULL,
+    `contactEmail` VARCHAR(191) NULL,
+    `contactPhone` VARCHAR(191) NULL,
+    `billingEmail` VARCHAR(191) NULL,
+    `billingAddress` TEXT NULL,
+    `taxId` VARCHAR(191) NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- CreateTable
+CREATE TABLE `Assignment` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `userId` INTEGER NOT NULL,
+    `employerId` INTEGER NOT NULL,
+    `startDate` DATETIME(3) NOT NULL,
+    `endDate` DATETIME(3) NULL,
+    `rate` DOUBLE NOT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- Move the free-text employer on existing work logs to Employer records
+ALTER TABLE `WorkLog` ADD COLUMN `employerId` INTEGER NULL;
+
+INSERT INTO `Employer` (`name`, `updatedAt`)
+    SELECT DISTINCT `employer`, CURRENT_TIMESTAMP(3) FROM `WorkLog`;
+
+UPDATE `WorkLog` INNER JOIN `Employer` ON `Employer`.`name` = `WorkLog`.`employer`
+    SET `WorkLog`.`employerId` = `Employer`.`id`;
+
+-- AlterTable
+ALTER TABLE `WorkLog` DROP COLUMN `employer`,
+    MODIFY `employerId` INTEGER NOT NULL;
+
+-- AddForeignKey
+ALTER TABLE `WorkLog` ADD CONSTRAINT `WorkLog_employerId_fkey` FOREIGN KEY (`employerId`) REFERENCES `Employer`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Assignment` ADD CONSTRAINT `Assignment_userId_fkey` FOREIGN KEY (`userId`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Assignment` ADD CONSTRAINT `Assignment_employerId_fkey` FOREIGN KEY (`employerId`) REFERENCES `Employer`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
@@ -0,0 +1,2 @@
+-- AlterTable
+ALTER TABLE `User` ADD COLUMN `role` ENUM('WORKER', 'SUPERVISOR', 'EMPLOYER', 'ADMIN') NOT NULL DEFAULT 'WORKER';
@@ -0,0 +1,69 @@
+-- CreateTable
+CREATE TABLE `Organization` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `name` VARCHAR(191) NOT NULL,
+    `slug` VARCHAR(191) NOT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    UNIQUE INDEX `Organization_slug_key`(`slug`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- CreateTable
+CREATE TABLE `Membership` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `userId` INTEGER NOT NULL,
+    `role` ENUM('WORKER', 'SUPERVISOR', 'EMPLOYER', 'ADMIN') NOT NULL DEFAULT 'WORKER',
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    UNIQUE INDEX `Membership_userId_organizationId_key`(`userId`, `organizationId`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- Existing data moves into a default organization, keeping every user's role
+INSERT INTO `Organization` (`name`, `slug`, `updatedAt`)
+    VALUES ('Default', 'default', CURRENT_TIMESTAMP(3));
+
+SET @default_org_id = LAST_INSERT_ID();
+
+INSERT INTO `Membership` (`organizationId`, `userId`, `role`, `updatedAt`)
+    SELECT @default_org_id, `id`, `role`, CURRENT_TIMESTAMP(3) FROM `User`;
+
+-- AlterTable
+ALTER TABLE `Session` ADD COLUMN `organizationId` INTEGER NULL;
+
+-- AlterTable
+ALTER TABLE `Employer` ADD COLUMN `organizationId` INTEGER NULL;
+UPDATE `Employer` SET `organizationId` = @default_org_id;
+ALTER TABLE `Employer` MODIFY `organizationId` INTEGER NOT NULL;
+
+-- AlterTable
+ALTER TABLE `Assignment` ADD COLUMN `organizationId` INTEGER NULL;
+UPDATE `Assignment` SET `organizationId` = @default_org_id;
+ALTER TABLE `Assignment` MODIFY `organizationId` INTEGER NOT NULL;
+
+-- AlterTable
+ALTER TABLE `WorkLog` ADD COLUMN `organizationId` INTEGER NULL;
+UPDATE `WorkLog` SET `organizationId` = @default_org_id;
+ALTER TABLE `WorkLog` MODIFY `organizationId` INTEGER NOT NULL;
+
+-- AddForeignKey
+ALTER TABLE `Membership` ADD CONSTRAINT `Membership_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Membership` ADD CONSTRAINT `Membership_userId_fkey` FOREIGN KEY (`userId`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Session` ADD CONSTRAINT `Session_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Employer` ADD CONSTRAINT `Employer_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Assignment` ADD CONSTRAINT `Assignment_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `WorkLog` ADD CONSTRAINT `WorkLog_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
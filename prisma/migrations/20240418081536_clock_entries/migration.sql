@@ -0,0 +1,32 @@
+-- CreateTable
+CREATE TABLE `ClockEntry` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `workerId` INTEGER NOT NULL,
+    `employerId` INTEGER NOT NULL,
+    `jobType` VARCHAR(191) NOT NULL,
+    `notes` TEXT NULL,
+    `clockInAt` DATETIME(3) NOT NULL,
+    `clockOutAt` DATETIME(3) NULL,
+    `autoClosed` BOOLEAN NOT NULL DEFAULT false,
+    `openForWorkerId` INTEGER NULL,
+    `workLogId` INTEGER NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    UNIQUE INDEX `ClockEntry_openForWorkerId_key`(`openForWorkerId`),
+    UNIQUE INDEX `ClockEntry_workLogId_key`(`workLogId`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `ClockEntry` ADD CONSTRAINT `ClockEntry_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `ClockEntry` ADD CONSTRAINT `ClockEntry_workerId_fkey` FOREIGN KEY (`workerId`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `ClockEntry` ADD CONSTRAINT `ClockEntry_employerId_fkey` FOREIGN KEY (`employerId`) REFERENCES `Employer`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `ClockEntry` ADD CONSTRAINT `ClockEntry_workLogId_fkey` FOREIGN KEY (`workLogId`) REFERENCES `WorkLog`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,30 @@
+-- CreateTable
+CREATE TABLE `HarvestTally` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `workerId` INTEGER NOT NULL,
+    `employerId` INTEGER NOT NULL,
+    `crop` VARCHAR(191) NOT NULL,
+    `unit` ENUM('BIN', 'CRATE', 'KILOGRAM') NOT NULL,
+    `quantity` DOUBLE NOT NULL,
+    `recordedById` INTEGER NOT NULL,
+    `recordedAt` DATETIME(3) NOT NULL,
+    `notes` TEXT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    INDEX `HarvestTally_organizationId_recordedAt_idx`(`organizationId`, `recordedAt`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `HarvestTally` ADD CONSTRAINT `HarvestTally_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `HarvestTally` ADD CONSTRAINT `HarvestTally_workerId_fkey` FOREIGN KEY (`workerId`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `HarvestTally` ADD CONSTRAINT `HarvestTally_employerId_fkey` FOREIGN KEY (`employerId`) REFERENCES `Employer`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `HarvestTally` ADD CONSTRAINT `HarvestTally_recordedById_fkey` FOREIGN KEY (`recordedById`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
@@ -0,0 +1,87 @@
+-- AlterTable
+ALTER TABLE `HarvestTally` ADD COLUMN `payrollRunId` INTEGER NULL;
+
+-- AlterTable
+ALTER TABLE `WorkLog` ADD COLUMN `payrollRunId` INTEGER NULL;
+
+-- CreateTable
+CREATE TABLE `PieceRate` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `employerId` INTEGER NOT NULL,
+    `crop` VARCHAR(191) NOT NULL,
+    `unit` ENUM('BIN', 'CRATE', 'KILOGRAM') NOT NULL,
+    `rate` DOUBLE NOT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    UNIQUE INDEX `PieceRate_employerId_crop_unit_key`(`employerId`, `crop`, `unit`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- CreateTable
+CREATE TABLE `PayrollRun` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `periodStart` DATETIME(3) NOT NULL,
+    `periodEnd` DATETIME(3) NOT NULL,
+    `status` ENUM('DRAFT', 'FINALIZED') NOT NULL DEFAULT 'DRAFT',
+    `overtimeThresholdMinutes` INTEGER NOT NULL,
+    `overtimeMultiplier` DOUBLE NOT NULL,
+    `deductionRate` DOUBLE NOT NULL,
+    `totalGross` DOUBLE NOT NULL,
+    `totalDeductions` DOUBLE NOT NULL,
+    `totalNet` DOUBLE NOT NULL,
+    `createdById` INTEGER NOT NULL,
+    `finalizedById` INTEGER NULL,
+    `finalizedAt` DATETIME(3) NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- CreateTable
+CREATE TABLE `PayrollLineItem` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `payrollRunId` INTEGER NOT NULL,
+    `workerId` INTEGER NOT NULL,
+    `regularMinutes` INTEGER NOT NULL,
+    `overtimeMinutes` INTEGER NOT NULL,
+    `hourlyPay` DOUBLE NOT NULL,
+    `overtimePay` DOUBLE NOT NULL,
+    `piecePay` DOUBLE NOT NULL,
+    `grossPay` DOUBLE NOT NULL,
+    `deductions` DOUBLE NOT NULL,
+    `netPay` DOUBLE NOT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `WorkLog` ADD CONSTRAINT `WorkLog_payrollRunId_fkey` FOREIGN KEY (`payrollRunId`) REFERENCES `PayrollRun`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `HarvestTally` ADD CONSTRAINT `HarvestTally_payrollRunId_fkey` FOREIGN KEY (`payrollRunId`) REFERENCES `PayrollRun`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PieceRate` ADD CONSTRAINT `PieceRate_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PieceRate` ADD CONSTRAINT `PieceRate_employerId_fkey` FOREIGN KEY (`employerId`) REFERENCES `Employer`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PayrollRun` ADD CONSTRAINT `PayrollRun_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PayrollRun` ADD CONSTRAINT `PayrollRun_createdById_fkey` FOREIGN KEY (`createdById`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PayrollRun` ADD CONSTRAINT `PayrollRun_finalizedById_fkey` FOREIGN KEY (`finalizedById`) REFERENCES `User`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PayrollLineItem` ADD CONSTRAINT `PayrollLineItem_payrollRunId_fkey` FOREIGN KEY (`payrollRunId`) REFERENCES `PayrollRun`(`id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `PayrollLineItem` ADD CONSTRAINT `PayrollLineItem_workerId_fkey` FOREIGN KEY (`workerId`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
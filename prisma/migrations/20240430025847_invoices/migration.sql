@@ -0,0 +1,61 @@
+-- AlterTable
+ALTER TABLE `Organization` ADD COLUMN `nextInvoiceNumber` INTEGER NOT NULL DEFAULT 1;
+
+-- AlterTable
+ALTER TABLE `Employer` ADD COLUMN `hourlyBillingRate` DOUBLE NULL,
+    ADD COLUMN `taxRate` DOUBLE NOT NULL DEFAULT 0;
+
+-- AlterTable
+ALTER TABLE `PieceRate` ADD COLUMN `billingRate` DOUBLE NULL;
+
+-- CreateTable
+CREATE TABLE `Invoice` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `employerId` INTEGER NOT NULL,
+    `number` INTEGER NULL,
+    `status` ENUM('DRAFT', 'ISSUED', 'PAID', 'VOID') NOT NULL DEFAULT 'DRAFT',
+    `periodStart` DATETIME(3) NOT NULL,
+    `periodEnd` DATETIME(3) NOT NULL,
+    `taxRate` DOUBLE NOT NULL,
+    `subtotal` DOUBLE NOT NULL,
+    `tax` DOUBLE NOT NULL,
+    `total` DOUBLE NOT NULL,
+    `issuedAt` DATETIME(3) NULL,
+    `paidAt` DATETIME(3) NULL,
+    `voidedAt` DATETIME(3) NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    `updatedAt` DATETIME(3) NOT NULL,
+
+    UNIQUE INDEX `Invoice_organizationId_number_key`(`organizationId`, `number`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- CreateTable
+CREATE TABLE `InvoiceLine` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `invoiceId` INTEGER NOT NULL,
+    `workLogId` INTEGER NULL,
+    `harvestTallyId` INTEGER NULL,
+    `description` VARCHAR(191) NOT NULL,
+    `quantity` DOUBLE NOT NULL,
+    `unitPrice` DOUBLE NOT NULL,
+    `amount` DOUBLE NOT NULL,
+
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `Invoice` ADD CONSTRAINT `Invoice_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Invoice` ADD CONSTRAINT `Invoice_employerId_fkey` FOREIGN KEY (`employerId`) REFERENCES `Employer`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `InvoiceLine` ADD CONSTRAINT `InvoiceLine_invoiceId_fkey` FOREIGN KEY (`invoiceId`) REFERENCES `Invoice`(`id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `InvoiceLine` ADD CONSTRAINT `InvoiceLine_workLogId_fkey` FOREIGN KEY (`workLogId`) REFERENCES `WorkLog`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `InvoiceLine` ADD CONSTRAINT `InvoiceLine_harvestTallyId_fkey` FOREIGN KEY (`harvestTallyId`) REFERENCES `HarvestTally`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
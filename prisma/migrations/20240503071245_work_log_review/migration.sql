@@ -0,0 +1,12 @@
+-- AlterTable
+ALTER TABLE `WorkLog` ADD COLUMN `status` ENUM('SUBMITTED', 'APPROVED', 'REJECTED', 'AMENDED') NOT NULL DEFAULT 'SUBMITTED',
+    ADD COLUMN `reviewedById` INTEGER NULL,
+    ADD COLUMN `reviewedAt` DATETIME(3) NULL,
+    ADD COLUMN `rejectionReason` TEXT NULL;
+
+-- Work logged before the review workflow existed has already been paid and
+-- billed, so it counts as approved
+UPDATE `WorkLog` SET `status` = 'APPROVED';
+
+-- AddForeignKey
+ALTER TABLE `WorkLog` ADD CONSTRAINT `WorkLog_reviewedById_fkey` FOREIGN KEY (`reviewedById`) REFERENCES `User`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,25 @@
+-- CreateTable
+CREATE TABLE `Attachment` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `organizationId` INTEGER NOT NULL,
+    `workLogId` INTEGER NOT NULL,
+    `uploadedById` INTEGER NOT NULL,
+    `kind` ENUM('PHOTO', 'TIMESHEET', 'DAMAGE_REPORT', 'OTHER') NOT NULL DEFAULT 'OTHER',
+    `fileName` VARCHAR(191) NOT NULL,
+    `contentType` VARCHAR(191) NOT NULL,
+    `size` INTEGER NOT NULL,
+    `storageKey` VARCHAR(191) NOT NULL,
+    `createdAt` DATETIME(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    UNIQUE INDEX `Attachment_storageKey_key`(`storageKey`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+-- AddForeignKey
+ALTER TABLE `Attachment` ADD CONSTRAINT `Attachment_organizationId_fkey` FOREIGN KEY (`organizationId`) REFERENCES `Organization`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Attachment` ADD CONSTRAINT `Attachment_workLogId_fkey` FOREIGN KEY (`workLogId`) REFERENCES `WorkLog`(`id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE `Attachment` ADD CONSTRAINT `Attachment_uploadedById_fkey` FOREIGN KEY (`uploadedById`) REFERENCES `User`(`id`) ON DELETE RESTRICT ON UPDATE CASCADE;
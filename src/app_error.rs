@@ -1,12 +1,18 @@
 use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
 use prisma_client_rust::{
     prisma_errors::query_engine::{RecordNotFound, UniqueKeyViolation},
     QueryError,
 };
-use tracing::error;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{debug, error};
+
+use crate::config::app_env;
 
 #[derive(thiserror::Error, Debug)]
 pub enum AppError {
@@ -19,31 +25,194 @@ pub enum AppError {
     #[error("Unauthorized : {0}")]
     Unauthorized(String),
 
+    #[error("Forbidden : {0}")]
+    Forbidden(String),
+
     #[error("Bad request : {0}")]
     BadRequest(String),
 
+    #[error("Conflict : {0}")]
+    Conflict(String),
+
+    #[error("Validation failed : {0:?}")]
+    Validation(Vec<FieldError>),
+
+    #[error("Too many requests : {0}")]
+    TooManyRequests(String),
+
     #[error("Internal server error: {0}")]
     Anyhow(#[from] anyhow::Error),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let status = match self {
-            AppError::PrismaError(ref error) if error.is_prisma_error::<UniqueKeyViolation>() => {
+/// A problem with a single input field, reported under `details` of a
+/// `VALIDATION_FAILED` error.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: String::from(field),
+            message: String::from(message),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail {
+    code: &'static str,
+    message: String,
+    details: Option<Value>,
+}
+
+/// Builds the `{ "error": { code, message, details } }` envelope shared by
+/// every error response, including those produced outside of `AppError`.
+pub fn error_response(
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    details: Option<Value>,
+) -> Response {
+    let body = ErrorBody {
+        error: ErrorDetail {
+            code,
+            message,
+            details,
+        },
+    };
+
+    (status, Json(body)).into_response()
+}
+
+impl AppError {
+    /// Machine-readable code clients can branch on. These are part of the
+    /// API contract, so existing codes must not be renamed.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::PrismaError(error) if error.is_prisma_error::<UniqueKeyViolation>() => {
+                "CONFLICT"
+            }
+            AppError::PrismaError(error) if error.is_prisma_error::<RecordNotFound>() => {
+                "NOT_FOUND"
+            }
+            AppError::PrismaError(_) => "DATABASE_ERROR",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Validation(_) => "VALIDATION_FAILED",
+            AppError::TooManyRequests(_) => "TOO_MANY_REQUESTS",
+            AppError::Anyhow(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::PrismaError(error) if error.is_prisma_error::<UniqueKeyViolation>() => {
                 StatusCode::CONFLICT
             }
-            AppError::PrismaError(ref error) if error.is_prisma_error::<RecordNotFound>() => {
+            AppError::PrismaError(error) if error.is_prisma_error::<RecordNotFound>() => {
                 StatusCode::NOT_FOUND
             }
-            AppError::PrismaError(_) => StatusCode::BAD_REQUEST,
+            AppError::PrismaError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
             AppError::Anyhow(_) => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            AppError::PrismaError(error) if error.is_prisma_error::<UniqueKeyViolation>() => {
+                String::from("Resource already exists")
+            }
+            AppError::PrismaError(error) if error.is_prisma_error::<RecordNotFound>() => {
+                String::from("Resource not found")
+            }
+            AppError::PrismaError(error) => error.to_string(),
+            AppError::NotFound(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::BadRequest(message)
+            | AppError::Conflict(message)
+            | AppError::TooManyRequests(message) => message.clone(),
+            AppError::Validation(_) => String::from("Request validation failed"),
+            AppError::Anyhow(error) => error.to_string(),
+        }
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
 
-        error!("{:?}", self);
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        match rejection {
+            PathRejection::FailedToDeserializePathParams(_) => {
+                AppError::BadRequest(rejection.body_text())
+            }
+            // The route and the handler disagree, which is a bug on our side
+            _ => AppError::Anyhow(anyhow::anyhow!(rejection.body_text())),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+
+        if status.is_server_error() {
+            // The id ties the client-visible response to the full error in
+            // the logs, which is the only place the details end up in
+            // production.
+            let correlation_id = hex::encode(rand::random::<[u8; 8]>());
+            error!(correlation_id, "{:?}", self);
+
+            let message = if app_env().is_production() {
+                String::from("Internal server error")
+            } else {
+                self.message()
+            };
+
+            return error_response(
+                status,
+                code,
+                message,
+                Some(json!({ "correlationId": correlation_id })),
+            );
+        }
+
+        debug!("{:?}", self);
+
+        let details = match &self {
+            AppError::Validation(errors) => Some(json!(errors)),
+            _ => None,
+        };
 
-        (status, self.to_string()).into_response()
+        error_response(status, code, self.message(), details)
     }
 }
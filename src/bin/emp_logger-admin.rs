@@ -0,0 +1,359 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use anyhow::{bail, Context};
+use clap::{Parser, Subcommand};
+use emp_logger::{
+    config::{app_config::AppConfig, AppContext},
+    domain::{sessions::service::SessionsService, users::service::UsersService},
+    prisma::{
+        clock_entry, membership, organization, session, user, work_log, PrismaClient, Role,
+        WorkLogStatus,
+    },
+    storage,
+    validation::rules,
+};
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
+use rand::{rngs::OsRng, RngCore};
+
+/// Maintenance tasks that would otherwise need raw SQL against the
+/// production database. Reads the same configuration as the server.
+#[derive(Parser)]
+#[command(name = "emp_logger-admin", version)]
+struct Cli {
+    /// Config file to read instead of `CONFIG_FILE` or `config.toml`
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a verified admin user with a generated password
+    CreateAdmin {
+        username: String,
+
+        #[arg(long)]
+        email: Option<String>,
+
+        /// Also make the user an admin of this organization
+        #[arg(long)]
+        org: Option<String>,
+    },
+
+    /// Replace a user's password with a generated one and end their sessions
+    ResetPassword { username: String },
+
+    /// Mark a user's email as verified
+    Verify { username: String },
+
+    /// Soft-delete a user and end their sessions
+    Delete { username: String },
+
+    /// Undo a soft delete
+    Restore { username: String },
+
+    /// Generate a new JWT signing secret
+    RotateJwtSecret {
+        /// Write the secret to this env file instead of printing it
+        #[arg(long)]
+        env_file: Option<PathBuf>,
+
+        /// Also revoke every session, which signs everybody out
+        #[arg(long)]
+        revoke_sessions: bool,
+    },
+
+    /// Print counts of users, organizations and records
+    Stats,
+}
+
+const JWT_SECRET_BYTES: usize = 64;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let config = AppConfig::load(cli.config.as_deref())?;
+    let ctx = AppContext {
+        config: Arc::new(config.clone()),
+        storage: storage::from_config(&config.storage)?,
+    };
+
+    let prisma = PrismaClient::_builder()
+        .with_url(config.db.url.clone())
+        .build()
+        .await?;
+
+    match cli.command {
+        Command::CreateAdmin {
+            username,
+            email,
+            org,
+        } => create_admin(&prisma, username, email, org).await,
+        Command::ResetPassword { username } => {
+            let data = find_user(&prisma, &username).await?;
+            let password = UsersService::generate_password();
+
+            prisma
+                .user()
+                .update(
+                    user::id::equals(data.id),
+                    vec![user::password::set(UsersService::hash_password(&password)?)],
+                )
+                .exec()
+                .await?;
+            SessionsService::revoke_all(&prisma, data.id).await?;
+
+            println!("New password for {}: {}", data.username, password);
+            Ok(())
+        }
+        Command::Verify { username } => {
+            let data = find_user(&prisma, &username).await?;
+            if data.email.is_none() {
+                bail!("{} has no email address to verify", data.username);
+            }
+
+            prisma
+                .user()
+                .update(user::id::equals(data.id), vec![user::verified::set(true)])
+                .exec()
+                .await?;
+
+            println!("Verified {}", data.username);
+            Ok(())
+        }
+        Command::Delete { username } => {
+            let data = find_user(&prisma, &username).await?;
+            if data.deleted_at.is_some() {
+                bail!("{} is already deleted", data.username);
+            }
+
+            UsersService::deactivate(&prisma, data.id).await?;
+
+            println!("Deleted {}", data.username);
+            Ok(())
+        }
+        Command::Restore { username } => {
+            let data = find_user(&prisma, &username).await?;
+            if data.deleted_at.is_none() {
+                bail!("{} is not deleted", data.username);
+            }
+
+            UsersService::restore(&prisma, &ctx, &data).await?;
+
+            println!("Restored {}", data.username);
+            Ok(())
+        }
+        Command::RotateJwtSecret {
+            env_file,
+            revoke_sessions,
+        } => rotate_jwt_secret(&prisma, env_file, revoke_sessions).await,
+        Command::Stats => stats(&prisma).await,
+    }
+}
+
+async fn create_admin(
+    prisma: &PrismaClient,
+    username: String,
+    email: Option<String>,
+    org: Option<String>,
+) -> anyhow::Result<()> {
+    if let Err(message) = rules::username(&username) {
+        bail!("username: {}", message);
+    }
+    if let Some(email) = &email {
+        if let Err(message) = rules::email(email) {
+            bail!("email: {}", message);
+        }
+    }
+
+    let organization = match org {
+        Some(slug) => Some(
+            prisma
+                .organization()
+                .find_unique(organization::slug::equals(slug.clone()))
+                .exec()
+                .await?
+                .with_context(|| format!("no organization with the slug {}", slug))?,
+        ),
+        None => None,
+    };
+
+    if prisma
+        .user()
+        .find_unique(user::username::equals(username.clone()))
+        .exec()
+        .await?
+        .is_some()
+    {
+        bail!("username {} is already taken", username);
+    }
+
+    let password = UsersService::generate_password();
+    let hash = UsersService::hash_password(&password)?;
+    let verified = email.is_some();
+
+    let data = prisma
+        ._transaction()
+        .run(|tx| async move {
+            let data = tx
+                .user()
+                .create(
+                    hash,
+                    username,
+                    vec![
+                        user::email::set(email),
+                        user::verified::set(verified),
+                        user::role::set(Role::Admin),
+                    ],
+                )
+                .exec()
+                .await?;
+
+            if let Some(organization) = organization {
+                tx.membership()
+                    .create(
+                        organization::id::equals(organization.id),
+                        user::id::equals(data.id),
+                        vec![membership::role::set(Role::Admin)],
+                    )
+                    .exec()
+                    .await?;
+            }
+
+            Ok::<_, prisma_client_rust::QueryError>(data)
+        })
+        .await?;
+
+    println!("Created admin {} with password {}", data.username, password);
+    Ok(())
+}
+
+/// The server only reads the secret at startup, so this changes nothing
+/// until it is restarted. Access tokens signed with the old secret are then
+/// rejected, and clients get new ones with their refresh tokens, which do
+/// not depend on the secret.
+async fn rotate_jwt_secret(
+    prisma: &PrismaClient,
+    env_file: Option<PathBuf>,
+    revoke_sessions: bool,
+) -> anyhow::Result<()> {
+    let mut bytes = [0u8; JWT_SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let secret = hex::encode(bytes);
+
+    match env_file {
+        Some(path) => {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            fs::write(&path, replace_env_var(&contents, "JWT_SECRET", &secret))
+                .with_context(|| format!("failed to write {}", path.display()))?;
+
+            println!("Wrote a new JWT_SECRET to {}", path.display());
+        }
+        None => println!("JWT_SECRET={}", secret),
+    }
+
+    if revoke_sessions {
+        let now: DateTime<FixedOffset> = chrono::Utc::now().into();
+        let revoked = prisma
+            .session()
+            .update_many(
+                vec![session::revoked_at::equals(None)],
+                vec![session::revoked_at::set(Some(now))],
+            )
+            .exec()
+            .await?;
+
+        println!("Revoked {} sessions", revoked);
+    }
+
+    eprintln!("Restart the servers to start signing with the new secret");
+    Ok(())
+}
+
+async fn stats(prisma: &PrismaClient) -> anyhow::Result<()> {
+    let now: DateTime<FixedOffset> = chrono::Utc::now().into();
+
+    let users = prisma.user().count(vec![]).exec().await?;
+    let deleted = prisma
+        .user()
+        .count(vec![user::deleted_at::not(None)])
+        .exec()
+        .await?;
+    let verified = prisma
+        .user()
+        .count(vec![user::verified::equals(true)])
+        .exec()
+        .await?;
+    let organizations = prisma.organization().count(vec![]).exec().await?;
+    let sessions = prisma
+        .session()
+        .count(vec![
+            session::revoked_at::equals(None),
+            session::expires_at::gt(now),
+        ])
+        .exec()
+        .await?;
+    let open_shifts = prisma
+        .clock_entry()
+        .count(vec![clock_entry::clock_out_at::equals(None)])
+        .exec()
+        .await?;
+
+    println!("Users:              {}", users);
+    println!("  verified:         {}", verified);
+    println!("  deleted:          {}", deleted);
+    println!("Organizations:      {}", organizations);
+    println!("Active sessions:    {}", sessions);
+    println!("Open shifts:        {}", open_shifts);
+    println!("Work logs:");
+    for (label, status) in [
+        ("submitted", WorkLogStatus::Submitted),
+        ("amended", WorkLogStatus::Amended),
+        ("approved", WorkLogStatus::Approved),
+        ("rejected", WorkLogStatus::Rejected),
+    ] {
+        let count = prisma
+            .work_log()
+            .count(vec![work_log::status::equals(status)])
+            .exec()
+            .await?;
+        println!("  {:<17} {}", format!("{}:", label), count);
+    }
+
+    Ok(())
+}
+
+async fn find_user(prisma: &PrismaClient, username: &str) -> anyhow::Result<user::Data> {
+    prisma
+        .user()
+        .find_unique(user::username::equals(username.to_string()))
+        .exec()
+        .await?
+        .with_context(|| format!("no user with the username {}", username))
+}
+
+/// Sets `key` in the contents of an env file, keeping every other line.
+fn replace_env_var(contents: &str, key: &str, value: &str) -> String {
+    let prefix = format!("{}=", key);
+    let mut replaced = false;
+
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with(&prefix) {
+                replaced = true;
+                format!("{}{}", prefix, value)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(format!("{}{}", prefix, value));
+    }
+
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,132 @@
+use std::{path::PathBuf, process::ExitCode, sync::Arc};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use emp_logger::{
+    config::app_config::AppConfig,
+    domain::imports::{response::ImportReport, service::ImportsService},
+    prisma::{organization, user, PrismaClient},
+    tenant::TenantClient,
+};
+
+/// Imports users and historical work entries from CSV files. Every run is a
+/// dry run that only reports problems unless `--commit` is given. Reads the
+/// same configuration as the server.
+#[derive(Parser)]
+#[command(name = "emp_logger-import", version)]
+struct Cli {
+    /// Config file to read instead of `CONFIG_FILE` or `config.toml`
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Slug of the organization to import into
+    #[arg(long)]
+    org: String,
+
+    /// Store the rows, if every row is valid
+    #[arg(long)]
+    commit: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Users, with the columns username, first_name, last_name and email
+    Users { file: PathBuf },
+
+    /// Work entries, with the columns username, employer, job_type,
+    /// start_time, end_time, break_minutes and notes
+    WorkLogs {
+        file: PathBuf,
+
+        /// Username recorded as the approver of the imported entries
+        #[arg(long)]
+        reviewer: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<ExitCode> {
+    let cli = Cli::parse();
+    let config = AppConfig::load(cli.config.as_deref())?;
+
+    let prisma = Arc::new(
+        PrismaClient::_builder()
+            .with_url(config.db.url.clone())
+            .build()
+            .await?,
+    );
+    let organization = prisma
+        .organization()
+        .find_unique(organization::slug::equals(cli.org.clone()))
+        .exec()
+        .await?
+        .with_context(|| format!("no organization with the slug {}", cli.org))?;
+    let tenant = TenantClient::new(prisma.clone(), organization.id);
+
+    let report = match cli.command {
+        Command::Users { file } => {
+            let input = read(&file)?;
+            ImportsService::run_user_import(&prisma, &tenant, &input, cli.commit).await?
+        }
+        Command::WorkLogs { file, reviewer } => {
+            let reviewer_id = match reviewer {
+                Some(username) => Some(
+                    tenant
+                        .users()
+                        .find_first(vec![user::username::equals(username.clone())])
+                        .exec()
+                        .await?
+                        .with_context(|| format!("no member with the username {}", username))?
+                        .id,
+                ),
+                None => None,
+            };
+
+            let input = read(&file)?;
+            ImportsService::run_work_log_import(&prisma, &tenant, reviewer_id, &input, cli.commit)
+                .await?
+        }
+    };
+
+    print_report(&report, cli.commit);
+
+    Ok(if report.errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn read(file: &PathBuf) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(file).with_context(|| format!("failed to read {}", file.display()))
+}
+
+/// Problems go to stderr; generated credentials go to stdout as CSV so they
+/// can be redirected into a file.
+fn print_report(report: &ImportReport, commit: bool) {
+    for error in &report.errors {
+        eprintln!("line {}: {}: {}", error.row, error.field, error.message);
+    }
+    eprintln!(
+        "{} of {} rows are valid",
+        report.valid_rows, report.total_rows
+    );
+
+    if report.committed {
+        eprintln!("Imported {} rows", report.valid_rows);
+    } else if commit {
+        eprintln!("Nothing was imported, fix the rows above and run again");
+    } else {
+        eprintln!("Dry run, nothing was imported. Run again with --commit to import");
+    }
+
+    if !report.credentials.is_empty() {
+        println!("username,password");
+        for credential in &report.credentials {
+            println!("{},{}", credential.username, credential.password);
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use super::app_config::serialize_duration;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountsConfig {
+    /// Deleted accounts can be restored for this long, then their username
+    /// and email are released for reuse.
+    #[serde(serialize_with = "serialize_duration")]
+    pub deletion_grace: Duration,
+    /// How often the background task looks for accounts to release.
+    #[serde(serialize_with = "serialize_duration")]
+    pub release_interval: Duration,
+}
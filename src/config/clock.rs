@@ -0,0 +1,14 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use super::app_config::serialize_duration;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClockConfig {
+    /// Open shifts older than this are closed automatically.
+    #[serde(serialize_with = "serialize_duration")]
+    pub max_shift: Duration,
+    /// How often the background task looks for forgotten shifts.
+    #[serde(serialize_with = "serialize_duration")]
+    pub sweep_interval: Duration,
+}
@@ -1,5 +1,13 @@
-#[derive(Debug, Clone)]
+use serde::Serialize;
+use std::time::Duration;
+
+use super::app_config::serialize_duration;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct JwtConfig {
     pub secret: String,
-    pub exp_in_sec: i64,
+    #[serde(serialize_with = "serialize_duration")]
+    pub exp: Duration,
+    #[serde(serialize_with = "serialize_duration")]
+    pub refresh_exp: Duration,
 }
@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsConfig {
+    /// Serve `/metrics` on this port instead of the API port.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
@@ -1,15 +1,31 @@
-use std::sync::Arc;
-use lazy_static::lazy_static;
+use std::sync::{Arc, OnceLock};
 
-use self::app_config::AppConfig;
+use crate::storage::Storage;
 
+use self::{app_config::AppConfig, environment::Environment};
+
+pub mod accounts;
 pub mod app_config;
+pub mod clock;
 pub mod db;
+pub mod environment;
 pub mod jwt;
+pub mod metrics;
 pub mod otp;
 pub mod smtp;
+pub mod sources;
+pub mod storage;
 
 #[derive(Clone)]
 pub struct AppContext {
     pub config: Arc<AppConfig>,
-}
\ No newline at end of file
+    pub storage: Arc<dyn Storage>,
+}
+
+static APP_ENV: OnceLock<Environment> = OnceLock::new();
+
+/// Deployment environment, readable where no `AppContext` is at hand. Set
+/// when the configuration is loaded, development until then.
+pub fn app_env() -> Environment {
+    APP_ENV.get().copied().unwrap_or_default()
+}
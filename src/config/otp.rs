@@ -1,6 +1,12 @@
-#[derive(Debug, Clone)]
+use serde::Serialize;
+use std::time::Duration;
 
+use super::app_config::serialize_duration;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct OTPConfig {
     pub secret: String,
-    pub exp_in_sec: i64,
-}
\ No newline at end of file
+    #[serde(serialize_with = "serialize_duration")]
+    pub exp: Duration,
+    pub max_attempts: i32,
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+/// Config file read when neither `--config` nor `CONFIG_FILE` names one.
+pub const DEFAULT_FILE: &str = "config.toml";
+
+/// Every setting, by its key in the config file, the environment variable
+/// that overrides it and its default. Which settings must be set is decided
+/// when they are read into `AppConfig`.
+pub const SETTINGS: &[(&str, &str, Option<&str>)] = &[
+    ("port", "PORT", Some("8000")),
+    ("log_level", "RUST_LOG", Some("info")),
+    ("environment", "APP_ENV", Some("development")),
+    ("db.url", "DATABASE_URL", None),
+    ("jwt.secret", "JWT_SECRET", None),
+    ("jwt.exp", "JWT_EXP", Some("15m")),
+    ("jwt.refresh_exp", "JWT_REFRESH_EXP", Some("30days")),
+    ("otp.secret", "OTP_SECRET", None),
+    ("otp.exp", "OTP_EXP", Some("5m")),
+    ("otp.max_attempts", "OTP_MAX_ATTEMPTS", Some("5")),
+    ("smtp.name", "SMTP_NAME", None),
+    ("smtp.email", "SMTP_EMAIL", None),
+    ("smtp.password", "SMTP_PASSWORD", None),
+    ("smtp.server", "SMTP_SERVER", None),
+    ("smtp.port", "SMTP_PORT", Some("587")),
+    ("metrics.port", "METRICS_PORT", None),
+    ("clock.max_shift", "CLOCK_MAX_SHIFT", Some("12h")),
+    ("clock.sweep_interval", "CLOCK_SWEEP_INTERVAL", Some("5m")),
+    (
+        "accounts.deletion_grace",
+        "ACCOUNT_DELETION_GRACE",
+        Some("30days"),
+    ),
+    (
+        "accounts.release_interval",
+        "ACCOUNT_RELEASE_INTERVAL",
+        Some("1h"),
+    ),
+    ("storage.driver", "STORAGE_DRIVER", Some("local")),
+    ("storage.public_url", "STORAGE_PUBLIC_URL", Some("/uploads")),
+    ("storage.root", "STORAGE_LOCAL_ROOT", Some("uploads")),
+    ("storage.bucket", "S3_BUCKET", None),
+    ("storage.region", "S3_REGION", Some("us-east-1")),
+    ("storage.endpoint", "S3_ENDPOINT", None),
+    ("storage.access_key", "S3_ACCESS_KEY", None),
+    ("storage.secret_key", "S3_SECRET_KEY", None),
+];
+
+/// Settings whose values are never repeated in error messages.
+pub const SECRETS: &[&str] = &[
+    "db.url",
+    "jwt.secret",
+    "otp.secret",
+    "smtp.password",
+    "storage.secret_key",
+];
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    DotEnv(String),
+    Env(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::DotEnv(var) => write!(f, "{} in .env", var),
+            Source::Env(var) => write!(f, "{}", var),
+        }
+    }
+}
+
+/// One problem with the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub key: String,
+    pub origin: Option<Source>,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(key: impl Into<String>, origin: Option<Source>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            origin,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)?;
+        match &self.origin {
+            Some(Source::Default) | None => Ok(()),
+            Some(origin) => write!(f, " (from {})", origin),
+        }
+    }
+}
+
+/// Every problem found while loading the configuration, so they can all be
+/// fixed in one go.
+#[derive(Debug, Clone, Error)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration")?;
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Raw setting values, layered so that each source overrides the ones added
+/// before it. Problems with a layer itself, like an unknown key in the config
+/// file, are kept until the values are read.
+#[derive(Debug, Clone)]
+pub struct Sources {
+    values: HashMap<&'static str, (String, Source)>,
+    errors: Vec<ConfigError>,
+}
+
+impl Sources {
+    /// Starts from the defaults in `SETTINGS`.
+    pub fn defaults() -> Self {
+        let values = SETTINGS
+            .iter()
+            .filter_map(|(key, _, default)| {
+                default.map(|default| (*key, (String::from(default), Source::Default)))
+            })
+            .collect();
+
+        Self {
+            values,
+            errors: vec![],
+        }
+    }
+
+    /// Reads every layer in order: the defaults, the config file, `.env` and
+    /// the process environment. The config file is `path`, `CONFIG_FILE` or,
+    /// if it exists, `config.toml`.
+    pub fn load(path: Option<&Path>) -> Self {
+        // `.env` only sets variables that are not already set, so whatever
+        // it adds is told apart from the real environment by a snapshot
+        let explicit: HashSet<String> = process_env().into_iter().map(|(name, _)| name).collect();
+        let dotenv_error = match dotenv::dotenv() {
+            Ok(_) | Err(dotenv::Error::Io(_)) => None,
+            Err(err) => Some(err),
+        };
+        let (env_vars, dotenv_vars): (Vec<_>, Vec<_>) = process_env()
+            .into_iter()
+            .partition(|(name, _)| explicit.contains(name));
+
+        let config_file = |vars: &[(String, String)]| {
+            vars.iter()
+                .find(|(name, _)| name == "CONFIG_FILE")
+                .map(|(_, value)| PathBuf::from(value))
+        };
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| config_file(&env_vars))
+            .or_else(|| config_file(&dotenv_vars))
+            .or_else(|| Some(PathBuf::from(DEFAULT_FILE)).filter(|path| path.exists()));
+
+        let mut sources = Self::defaults();
+        if let Some(path) = path {
+            match fs::read_to_string(&path) {
+                Ok(contents) => sources = sources.file(&path, &contents),
+                Err(err) => sources.errors.push(ConfigError::new(
+                    path.display().to_string(),
+                    None,
+                    format!("cannot read config file: {}", err),
+                )),
+            }
+        }
+        if let Some(err) = dotenv_error {
+            sources
+                .errors
+                .push(ConfigError::new(".env", None, err.to_string()));
+        }
+
+        sources.dotenv(dotenv_vars).env(env_vars)
+    }
+
+    /// Layers a TOML config file on top. Unknown keys are reported, since
+    /// they are most likely typos that would otherwise go unnoticed.
+    pub fn file(mut self, path: &Path, contents: &str) -> Self {
+        match contents.parse::<toml::Table>() {
+            Ok(table) => self.merge_table(path, "", table),
+            Err(err) => self.errors.push(ConfigError::new(
+                path.display().to_string(),
+                None,
+                err.to_string().trim_end(),
+            )),
+        }
+        self
+    }
+
+    /// Layers the variables of a `.env` file on top.
+    pub fn dotenv(self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.merge_vars(vars, Source::DotEnv)
+    }
+
+    /// Layers environment variables on top.
+    pub fn env(self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.merge_vars(vars, Source::Env)
+    }
+
+    /// The value of a setting and where it came from.
+    pub fn get(&self, key: &str) -> Option<&(String, Source)> {
+        self.values.get(key)
+    }
+
+    pub fn errors(&self) -> &[ConfigError] {
+        &self.errors
+    }
+
+    fn merge_table(&mut self, path: &Path, prefix: &str, table: toml::Table) {
+        for (name, value) in table {
+            let key = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            let origin = Some(Source::File(path.to_path_buf()));
+
+            let raw = match value {
+                toml::Value::Table(table) => {
+                    self.merge_table(path, &key, table);
+                    continue;
+                }
+                toml::Value::Array(_) => {
+                    self.errors
+                        .push(ConfigError::new(key, origin, "expected a single value"));
+                    continue;
+                }
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                toml::Value::Datetime(value) => value.to_string(),
+            };
+
+            match SETTINGS.iter().find(|(setting, _, _)| *setting == key) {
+                Some((key, _, _)) => {
+                    self.values
+                        .insert(*key, (raw, Source::File(path.to_path_buf())));
+                }
+                None => self
+                    .errors
+                    .push(ConfigError::new(key, origin, "unknown setting")),
+            }
+        }
+    }
+
+    fn merge_vars(
+        mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+        origin: fn(String) -> Source,
+    ) -> Self {
+        let vars: HashMap<String, String> = vars.into_iter().collect();
+
+        for (key, name, _) in SETTINGS {
+            if let Some(value) = vars.get(*name) {
+                self.values
+                    .insert(*key, (value.clone(), origin(name.to_string())));
+                continue;
+            }
+
+            // Durations used to be set as a number and a unit, e.g.
+            // JWT_EXP_VALUE=15 and JWT_EXP_UNIT=minutes
+            let value_name = format!("{}_VALUE", name);
+            let unit_name = format!("{}_UNIT", name);
+            match (vars.get(&value_name), vars.get(&unit_name)) {
+                (Some(value), Some(unit)) => {
+                    let duration = format!("{}{}", value.trim(), unit.trim());
+                    self.values.insert(*key, (duration, origin(value_name)));
+                }
+                (None, None) => {}
+                _ => self.errors.push(ConfigError::new(
+                    *key,
+                    Some(origin(value_name.clone())),
+                    format!("{} and {} must be set together", value_name, unit_name),
+                )),
+            }
+        }
+
+        self
+    }
+}
+
+/// The process environment. Variables that are not valid UTF-8 cannot be
+/// settings and are left out.
+fn process_env() -> Vec<(String, String)> {
+    env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect()
+}
@@ -0,0 +1,26 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageConfig {
+    #[serde(flatten)]
+    pub driver: StorageDriver,
+    /// URL prefix under which stored files are reachable, e.g. `/uploads`
+    /// or a CDN in front of the bucket.
+    pub public_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "driver", rename_all = "lowercase")]
+pub enum StorageDriver {
+    /// Files are written below `root` and served by the API itself.
+    Local { root: PathBuf },
+    /// Any S3-compatible object store, e.g. AWS S3 or a local MinIO.
+    S3 {
+        bucket: String,
+        region: String,
+        endpoint: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
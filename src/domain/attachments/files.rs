@@ -0,0 +1,93 @@
+/// Larger uploads are rejected.
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+const MAX_FILE_NAME_LEN: usize = 120;
+
+/// A file type attachments may have, recognised by its leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileType {
+    pub content_type: &'static str,
+    pub extension: &'static str,
+}
+
+const JPEG: FileType = FileType {
+    content_type: "image/jpeg",
+    extension: "jpg",
+};
+const PNG: FileType = FileType {
+    content_type: "image/png",
+    extension: "png",
+};
+const WEBP: FileType = FileType {
+    content_type: "image/webp",
+    extension: "webp",
+};
+const HEIC: FileType = FileType {
+    content_type: "image/heic",
+    extension: "heic",
+};
+const PDF: FileType = FileType {
+    content_type: "application/pdf",
+    extension: "pdf",
+};
+
+/// Photos from phones and scanned documents. The declared content type of
+/// an upload is ignored, so a script cannot be stored as an image.
+pub fn sniff(bytes: &[u8]) -> Option<FileType> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(JPEG)
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(PNG)
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(WEBP)
+    } else if bytes.len() >= 12
+        && &bytes[4..8] == b"ftyp"
+        && matches!(&bytes[8..12], b"heic" | b"heix" | b"mif1")
+    {
+        Some(HEIC)
+    } else if bytes.starts_with(b"%PDF-") {
+        Some(PDF)
+    } else {
+        None
+    }
+}
+
+/// Checks an upload and returns its type.
+pub fn check(bytes: &[u8]) -> Result<FileType, &'static str> {
+    if bytes.is_empty() {
+        return Err("Must not be empty");
+    }
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        return Err("File must be at most 10 MB");
+    }
+    sniff(bytes).ok_or("File must be a JPEG, PNG, WebP or HEIC image, or a PDF")
+}
+
+/// The client's file name without directories or characters that would
+/// need escaping in a `Content-Disposition` header. The extension always
+/// matches the sniffed type.
+pub fn file_name(name: Option<&str>, file_type: FileType) -> String {
+    let name = name
+        .and_then(|name| name.rsplit(|c| c == '/' || c == '\\').next())
+        .unwrap_or_default();
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+
+    let stem: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_FILE_NAME_LEN)
+        .collect();
+    let stem = stem.trim_matches(|c| c == '.' || c == ' ');
+
+    if stem.is_empty() {
+        format!("attachment.{}", file_type.extension)
+    } else {
+        format!("{}.{}", stem, file_type.extension)
+    }
+}
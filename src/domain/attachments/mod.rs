@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+pub mod files;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentBody<T> {
+    pub attachment: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentsBody<T> {
+    pub attachments: Vec<T>,
+}
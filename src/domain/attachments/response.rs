@@ -0,0 +1,35 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{attachment, AttachmentKind};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub id: i32,
+    pub work_log_id: i32,
+    pub uploaded_by_id: i32,
+    pub kind: AttachmentKind,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i32,
+    /// API path the file is downloaded from, with the same authorization.
+    pub url: String,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl From<attachment::Data> for Attachment {
+    fn from(data: attachment::Data) -> Self {
+        Self {
+            url: format!("/api/attachments/{}/file", data.id),
+            id: data.id,
+            work_log_id: data.work_log_id,
+            uploaded_by_id: data.uploaded_by_id,
+            kind: data.kind,
+            file_name: data.file_name,
+            content_type: data.content_type,
+            size: data.size,
+            created_at: data.created_at,
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use axum::{
+    extract::{multipart::MultipartError, Multipart, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use rand::{rngs::OsRng, Rng};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::{
+    app_error::{AppError, FieldError},
+    config::AppContext,
+    extractor::{roles, AuthUser, Json, Path, RoleRequirement},
+    prisma::{attachment, user, work_log, AttachmentKind, PrismaClient},
+    storage::Storage,
+    telemetry::Observe,
+    tenant::TenantClient,
+};
+
+use super::{files, response::Attachment, AttachmentBody, AttachmentsBody};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+pub struct AttachmentsService;
+
+impl AttachmentsService {
+    /// Attaches the file sent in the multipart field `file` to a work log.
+    /// An optional field `kind` says what it shows.
+    pub async fn upload_attachment(
+        Path(work_log_id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        ctx: State<AppContext>,
+        prisma: Prisma,
+        mut multipart: Multipart,
+    ) -> Result<Json<AttachmentBody<Attachment>>, AppError> {
+        let work_log = tenant.work_logs().find_by_id(work_log_id).await?;
+        Self::authorize(&auth_user, work_log.worker_id)?;
+
+        let mut upload = None;
+        let mut kind = AttachmentKind::Other;
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(Self::multipart_error)?
+        {
+            match field.name() {
+                Some("file") => {
+                    let name = field.file_name().map(String::from);
+                    let bytes = field.bytes().await.map_err(Self::multipart_error)?;
+                    upload = Some((name, bytes));
+                }
+                Some("kind") => {
+                    let value = field.text().await.map_err(Self::multipart_error)?;
+                    kind =
+                        serde_json::from_value(serde_json::Value::String(value)).map_err(|_| {
+                            Self::field_error(
+                                "kind",
+                                "Must be one of PHOTO, TIMESHEET, DAMAGE_REPORT or OTHER",
+                            )
+                        })?;
+                }
+                _ => {}
+            }
+        }
+
+        let (name, bytes) = upload.ok_or_else(|| Self::field_error("file", "Must not be empty"))?;
+        let file_type =
+            files::check(&bytes).map_err(|message| Self::field_error("file", message))?;
+        let file_name = files::file_name(name.as_deref(), file_type);
+        let size = bytes.len() as i32;
+
+        let key = format!(
+            "attachments/{}/{}/{:016x}.{}",
+            tenant.org_id(),
+            work_log.id,
+            OsRng.gen::<u64>(),
+            file_type.extension
+        );
+        ctx.storage.put(&key, file_type.content_type, bytes).await?;
+
+        let created = prisma
+            .attachment()
+            .create(
+                tenant.organization(),
+                work_log::id::equals(work_log.id),
+                user::id::equals(auth_user.user_id),
+                file_name,
+                file_type.content_type.to_string(),
+                size,
+                key.clone(),
+                vec![attachment::kind::set(kind)],
+            )
+            .observed()
+            .exec()
+            .await;
+
+        let data = match created {
+            Ok(data) => data,
+            Err(e) => {
+                // Leave no file behind that no record points at
+                Self::delete_files(ctx.storage.as_ref(), vec![key]).await;
+                return Err(e.into());
+            }
+        };
+
+        Ok(Json::from(AttachmentBody {
+            attachment: data.into(),
+        }))
+    }
+
+    pub async fn list_attachments(
+        Path(work_log_id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+    ) -> Result<Json<AttachmentsBody<Attachment>>, AppError> {
+        let work_log = tenant.work_logs().find_by_id(work_log_id).await?;
+        Self::authorize(&auth_user, work_log.worker_id)?;
+
+        let attachments = tenant
+            .attachments()
+            .find_many(vec![attachment::work_log_id::equals(work_log.id)])
+            .exec()
+            .await?
+            .into_iter()
+            .map(Attachment::from)
+            .collect();
+
+        Ok(Json::from(AttachmentsBody { attachments }))
+    }
+
+    pub async fn get_attachment(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+    ) -> Result<Json<AttachmentBody<Attachment>>, AppError> {
+        let data = Self::find_accessible(&tenant, &auth_user, id).await?;
+
+        Ok(Json::from(AttachmentBody {
+            attachment: data.into(),
+        }))
+    }
+
+    /// Sends the file itself. It is always a download, never rendered inline,
+    /// and with the type sniffed at upload.
+    pub async fn download_attachment(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        ctx: State<AppContext>,
+    ) -> Result<Response, AppError> {
+        let data = Self::find_accessible(&tenant, &auth_user, id).await?;
+        let bytes = ctx.storage.get(&data.storage_key).await?;
+
+        Ok((
+            [
+                (header::CONTENT_TYPE, data.content_type),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", data.file_name),
+                ),
+                (header::X_CONTENT_TYPE_OPTIONS, String::from("nosniff")),
+            ],
+            bytes,
+        )
+            .into_response())
+    }
+
+    /// Workers can only delete what they uploaded themselves.
+    pub async fn delete_attachment(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        ctx: State<AppContext>,
+    ) -> Result<StatusCode, AppError> {
+        let data = Self::find_accessible(&tenant, &auth_user, id).await?;
+
+        if data.uploaded_by_id != auth_user.user_id
+            && !auth_user.has_role(roles::Supervisor::ALLOWED)
+        {
+            return Err(AppError::Forbidden(String::from(
+                "Only supervisors can delete attachments uploaded by others",
+            )));
+        }
+
+        tenant.attachments().delete_by_id(data.id).await?;
+        Self::delete_files(ctx.storage.as_ref(), vec![data.storage_key]).await;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Removes stored files whose records are gone. Failures only leave
+    /// orphaned files behind, so they are logged.
+    pub async fn delete_files(storage: &dyn Storage, keys: Vec<String>) {
+        for key in keys {
+            if let Err(e) = storage.delete(&key).await {
+                warn!("Failed to delete attachment file {}: {:?}", key, e);
+            }
+        }
+    }
+
+    async fn find_accessible(
+        tenant: &TenantClient,
+        auth_user: &AuthUser,
+        id: i32,
+    ) -> Result<attachment::Data, AppError> {
+        let data = tenant.attachments().find_by_id(id).await?;
+        let work_log = tenant.work_logs().find_by_id(data.work_log_id).await?;
+        Self::authorize(auth_user, work_log.worker_id)?;
+
+        Ok(data)
+    }
+
+    /// Attachments belong to the worker whose day they document. Besides
+    /// them, the supervisors and admins of the organization have access.
+    fn authorize(auth_user: &AuthUser, worker_id: i32) -> Result<(), AppError> {
+        if worker_id == auth_user.user_id || auth_user.has_role(roles::Supervisor::ALLOWED) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(String::from(
+                "Only the worker and their supervisors can access these attachments",
+            )))
+        }
+    }
+
+    fn field_error(field: &str, message: &str) -> AppError {
+        AppError::Validation(vec![FieldError::new(field, message)])
+    }
+
+    fn multipart_error(error: MultipartError) -> AppError {
+        AppError::BadRequest(format!("Invalid upload: {}", error.body_text()))
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+pub mod request;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockEntryBody<T> {
+    pub clock_entry: T,
+}
@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockInInput {
+    /// Defaults to the employer of the worker's current assignment.
+    pub employer_id: Option<i32>,
+    pub job_type: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockOutInput {
+    pub break_minutes: Option<i32>,
+    pub notes: Option<String>,
+}
@@ -0,0 +1,38 @@
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::clock_entry;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockEntry {
+    pub id: i32,
+    pub employer_id: i32,
+    pub job_type: String,
+    pub notes: Option<String>,
+    pub clock_in_at: DateTime<FixedOffset>,
+    pub clock_out_at: Option<DateTime<FixedOffset>>,
+    pub elapsed_minutes: i64,
+    pub auto_closed: bool,
+    pub work_log_id: Option<i32>,
+}
+
+impl From<clock_entry::Data> for ClockEntry {
+    fn from(data: clock_entry::Data) -> Self {
+        let until = data
+            .clock_out_at
+            .unwrap_or_else(|| chrono::Utc::now().into());
+
+        Self {
+            elapsed_minutes: (until - data.clock_in_at).num_minutes().max(0),
+            id: data.id,
+            employer_id: data.employer_id,
+            job_type: data.job_type,
+            notes: data.notes,
+            clock_in_at: data.clock_in_at,
+            clock_out_at: data.clock_out_at,
+            auto_closed: data.auto_closed,
+            work_log_id: data.work_log_id,
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use axum::{extract::State, Extension};
+use prisma_client_rust::{
+    chrono::{self, DateTime, Duration, FixedOffset},
+    prisma_errors::query_engine::UniqueKeyViolation,
+    QueryError,
+};
+use std::sync::Arc;
+
+use crate::{
+    app_error::AppError,
+    config::AppContext,
+    domain::{employers::service::EmployersService, work_logs::service::WorkLogsService},
+    extractor::{AuthUser, Json},
+    prisma::{clock_entry, employer, organization, user, work_log, PrismaClient},
+    telemetry::{self, Observe},
+    tenant::TenantClient,
+};
+
+use super::{
+    request::{ClockInInput, ClockOutInput},
+    response::ClockEntry,
+    ClockEntryBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+const AUTO_CLOSED_NOTE: &str = "Shift was not clocked out and was closed automatically";
+
+pub struct ClockService;
+
+impl ClockService {
+    pub async fn clock_in(
+        auth_user: AuthUser,
+        ctx: State<AppContext>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<ClockEntryBody<ClockInInput>>,
+    ) -> Result<Json<ClockEntryBody<ClockEntry>>, AppError> {
+        let ClockEntryBody {
+            clock_entry:
+                ClockInInput {
+                    employer_id,
+                    job_type,
+                    notes,
+                },
+        } = input;
+
+        if job_type.trim().is_empty() {
+            return Err(AppError::BadRequest(String::from(
+                "Job type must not be empty",
+            )));
+        }
+
+        // The shift may be open in another of the worker's organizations,
+        // which they have to switch to for clocking out
+        if let Some(entry) = Self::open_entry_anywhere(&prisma, &ctx, auth_user.user_id).await? {
+            if entry.organization_id == tenant.org_id() {
+                return Err(Self::already_clocked_in());
+            }
+
+            let organization = entry
+                .organization()
+                .map(|organization| organization.name.as_str())
+                .unwrap_or("another organization");
+            return Err(AppError::Conflict(format!(
+                "You are already clocked in at {}",
+                organization
+            )));
+        }
+
+        let employer_id = match employer_id {
+            Some(employer_id) => tenant.employers().find_by_id(employer_id).await?.id,
+            None => EmployersService::current_assignment(&tenant, auth_user.user_id)
+                .await?
+                .map(|assignment| assignment.employer_id)
+                .ok_or(AppError::BadRequest(String::from(
+                    "No employer given and no current assignment",
+                )))?,
+        };
+
+        // The unique index on `openForWorkerId` settles concurrent punch-ins
+        // that both passed the check above.
+        let data = prisma
+            .clock_entry()
+            .create(
+                tenant.organization(),
+                user::id::equals(auth_user.user_id),
+                employer::id::equals(employer_id),
+                job_type,
+                Self::now(),
+                vec![
+                    clock_entry::notes::set(notes),
+                    clock_entry::open_for_worker_id::set(Some(auth_user.user_id)),
+                ],
+            )
+            .observed()
+            .exec()
+            .await
+            .map_err(|e| {
+                if e.is_prisma_error::<UniqueKeyViolation>() {
+                    Self::already_clocked_in()
+                } else {
+                    e.into()
+                }
+            })?;
+
+        Ok(Json::from(ClockEntryBody {
+            clock_entry: data.into(),
+        }))
+    }
+
+    pub async fn clock_out(
+        auth_user: AuthUser,
+        ctx: State<AppContext>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<ClockEntryBody<ClockOutInput>>,
+    ) -> Result<Json<ClockEntryBody<ClockEntry>>, AppError> {
+        let ClockEntryBody {
+            clock_entry:
+                ClockOutInput {
+                    break_minutes,
+                    notes,
+                },
+        } = input;
+
+        let entry = Self::open_entry(&tenant, &prisma, &ctx, auth_user.user_id)
+            .await?
+            .ok_or(AppError::Conflict(String::from("You are not clocked in")))?;
+
+        let clock_out_at = Self::now();
+        let break_minutes = break_minutes.unwrap_or(0);
+        WorkLogsService::validate_times(&entry.clock_in_at, &Some(clock_out_at), break_minutes)?;
+
+        let notes = notes.or(entry.notes.clone());
+        let data = Self::close(&prisma, &entry, clock_out_at, break_minutes, notes, false)
+            .await?
+            .ok_or(AppError::Conflict(String::from("You are not clocked in")))?;
+
+        Ok(Json::from(ClockEntryBody {
+            clock_entry: data.into(),
+        }))
+    }
+
+    pub async fn status(
+        auth_user: AuthUser,
+        ctx: State<AppContext>,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<ClockEntryBody<Option<ClockEntry>>>, AppError> {
+        let data = Self::open_entry(&tenant, &prisma, &ctx, auth_user.user_id).await?;
+
+        Ok(Json::from(ClockEntryBody {
+            clock_entry: data.map(ClockEntry::from),
+        }))
+    }
+
+    /// Closes every shift in every organization that has been open longer
+    /// than the configured limit. Run periodically from `main`; requests
+    /// also close the caller's own stale shift on the spot.
+    pub async fn close_stale_shifts(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+    ) -> Result<usize, AppError> {
+        let stale = prisma
+            .clock_entry()
+            .find_many(vec![
+                clock_entry::clock_out_at::equals(None),
+                clock_entry::clock_in_at::lt(Self::stale_before(ctx)),
+            ])
+            .observed()
+            .exec()
+            .await?;
+
+        let mut closed = 0;
+        for entry in stale {
+            if Self::auto_close(prisma, ctx, &entry).await?.is_some() {
+                closed += 1;
+            }
+        }
+
+        Ok(closed)
+    }
+
+    /// The worker's open shift, if any. A shift past the limit is closed
+    /// first and not returned.
+    async fn open_entry(
+        tenant: &TenantClient,
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        worker_id: i32,
+    ) -> Result<Option<clock_entry::Data>, AppError> {
+        let entry = tenant
+            .clock_entries()
+            .find_first(vec![
+                clock_entry::worker_id::equals(worker_id),
+                clock_entry::clock_out_at::equals(None),
+            ])
+            .exec()
+            .await?;
+
+        Self::unless_stale(prisma, ctx, entry).await
+    }
+
+    /// The worker's open shift in any organization, with the organization
+    /// fetched. Nobody works two shifts at once, so the unique index on
+    /// `openForWorkerId` spans organizations.
+    async fn open_entry_anywhere(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        worker_id: i32,
+    ) -> Result<Option<clock_entry::Data>, AppError> {
+        let entry = prisma
+            .clock_entry()
+            .find_first(vec![clock_entry::open_for_worker_id::equals(Some(
+                worker_id,
+            ))])
+            .with(clock_entry::organization::fetch())
+            .observed()
+            .exec()
+            .await?;
+
+        Self::unless_stale(prisma, ctx, entry).await
+    }
+
+    /// Closes `entry` if it is past the limit, in which case it is not
+    /// returned.
+    async fn unless_stale(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        entry: Option<clock_entry::Data>,
+    ) -> Result<Option<clock_entry::Data>, AppError> {
+        match entry {
+            Some(entry) if entry.clock_in_at < Self::stale_before(ctx) => {
+                Self::auto_close(prisma, ctx, &entry).await?;
+                Ok(None)
+            }
+            entry => Ok(entry),
+        }
+    }
+
+    async fn auto_close(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        entry: &clock_entry::Data,
+    ) -> Result<Option<clock_entry::Data>, AppError> {
+        let max_shift = Duration::seconds(ctx.config.clock.max_shift.as_secs() as i64);
+        let clock_out_at = entry.clock_in_at + max_shift;
+        let notes = match &entry.notes {
+            Some(notes) => format!("{}\n{}", notes, AUTO_CLOSED_NOTE),
+            None => String::from(AUTO_CLOSED_NOTE),
+        };
+
+        Self::close(prisma, entry, clock_out_at, 0, Some(notes), true).await
+    }
+
+    /// Closes the shift and records it as a work log in one transaction.
+    /// Returns `None` when another request closed it first.
+    async fn close(
+        prisma: &PrismaClient,
+        entry: &clock_entry::Data,
+        clock_out_at: DateTime<FixedOffset>,
+        break_minutes: i32,
+        notes: Option<String>,
+        auto_closed: bool,
+    ) -> Result<Option<clock_entry::Data>, AppError> {
+        let entry_id = entry.id;
+        let organization_id = entry.organization_id;
+        let worker_id = entry.worker_id;
+        let employer_id = entry.employer_id;
+        let job_type = entry.job_type.clone();
+        let clock_in_at = entry.clock_in_at;
+
+        let data = telemetry::observe_query(
+            "transaction.close",
+            prisma._transaction().run(|tx| async move {
+                let updated = tx
+                    .clock_entry()
+                    .update_many(
+                        vec![
+                            clock_entry::id::equals(entry_id),
+                            clock_entry::clock_out_at::equals(None),
+                        ],
+                        vec![
+                            clock_entry::clock_out_at::set(Some(clock_out_at)),
+                            clock_entry::open_for_worker_id::set(None),
+                            clock_entry::auto_closed::set(auto_closed),
+                            clock_entry::notes::set(notes.clone()),
+                        ],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+
+                if updated == 0 {
+                    return Ok::<_, QueryError>(None);
+                }
+
+                let work_log = tx
+                    .work_log()
+                    .create(
+                        organization::id::equals(organization_id),
+                        user::id::equals(worker_id),
+                        employer::id::equals(employer_id),
+                        job_type,
+                        clock_in_at,
+                        vec![
+                            work_log::end_time::set(Some(clock_out_at)),
+                            work_log::break_minutes::set(break_minutes),
+                            work_log::notes::set(notes),
+                        ],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+
+                let data = tx
+                    .clock_entry()
+                    .update(
+                        clock_entry::id::equals(entry_id),
+                        vec![clock_entry::work_log::connect(work_log::id::equals(
+                            work_log.id,
+                        ))],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+
+                Ok(Some(data))
+            }),
+        )
+        .await?;
+
+        Ok(data)
+    }
+
+    fn stale_before(ctx: &AppContext) -> DateTime<FixedOffset> {
+        Self::now() - Duration::seconds(ctx.config.clock.max_shift.as_secs() as i64)
+    }
+
+    fn already_clocked_in() -> AppError {
+        AppError::Conflict(String::from("You are already clocked in"))
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+pub mod request;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmployerBody<T> {
+    pub employer: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmployersBody<T> {
+    pub employers: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignmentBody<T> {
+    pub assignment: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignmentsBody<T> {
+    pub assignments: Vec<T>,
+}
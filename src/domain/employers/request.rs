@@ -0,0 +1,51 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmployerCreateInput {
+    pub name: String,
+    pub address: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub billing_email: Option<String>,
+    pub billing_address: Option<String>,
+    pub tax_id: Option<String>,
+    /// Charged per worked hour on invoices.
+    pub hourly_billing_rate: Option<f64>,
+    /// Fraction of the subtotal, e.g. `0.1` for 10%. Defaults to 0.
+    pub tax_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmployerUpdateInput {
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub billing_email: Option<String>,
+    pub billing_address: Option<String>,
+    pub tax_id: Option<String>,
+    pub hourly_billing_rate: Option<f64>,
+    pub tax_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignmentCreateInput {
+    pub username: String,
+    pub start_date: DateTime<FixedOffset>,
+    pub end_date: Option<DateTime<FixedOffset>>,
+    pub rate: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignmentUpdateInput {
+    pub start_date: Option<DateTime<FixedOffset>>,
+    pub end_date: Option<DateTime<FixedOffset>>,
+    pub rate: Option<f64>,
+}
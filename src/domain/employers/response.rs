@@ -0,0 +1,70 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{assignment, employer};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Employer {
+    pub id: i32,
+    pub name: String,
+    pub address: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub billing_email: Option<String>,
+    pub billing_address: Option<String>,
+    pub tax_id: Option<String>,
+    pub hourly_billing_rate: Option<f64>,
+    pub tax_rate: f64,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<employer::Data> for Employer {
+    fn from(data: employer::Data) -> Self {
+        Self {
+            id: data.id,
+            name: data.name,
+            address: data.address,
+            contact_name: data.contact_name,
+            contact_email: data.contact_email,
+            contact_phone: data.contact_phone,
+            billing_email: data.billing_email,
+            billing_address: data.billing_address,
+            tax_id: data.tax_id,
+            hourly_billing_rate: data.hourly_billing_rate,
+            tax_rate: data.tax_rate,
+            created_at: data.created_at,
+            updated_at: data.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Assignment {
+    pub id: i32,
+    pub user_id: i32,
+    pub employer_id: i32,
+    pub start_date: DateTime<FixedOffset>,
+    pub end_date: Option<DateTime<FixedOffset>>,
+    pub rate: f64,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<assignment::Data> for Assignment {
+    fn from(data: assignment::Data) -> Self {
+        Self {
+            id: data.id,
+            user_id: data.user_id,
+            employer_id: data.employer_id,
+            start_date: data.start_date,
+            end_date: data.end_date,
+            rate: data.rate,
+            created_at: data.created_at,
+            updated_at: data.updated_at,
+        }
+    }
+}
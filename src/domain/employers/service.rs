@@ -0,0 +1,375 @@
+use axum::Extension;
+use prisma_client_rust::{chrono, operator::or, Direction};
+use std::sync::Arc;
+
+use crate::{
+    app_error::AppError,
+    extractor::{roles, AuthUser, Json, Path, RequireRole},
+    prisma::{assignment, employer, user, PrismaClient},
+    telemetry::Observe,
+    tenant::TenantClient,
+};
+
+use super::{
+    request::{
+        AssignmentCreateInput, AssignmentUpdateInput, EmployerCreateInput, EmployerUpdateInput,
+    },
+    response::{Assignment, Employer},
+    AssignmentBody, AssignmentsBody, EmployerBody, EmployersBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+pub struct EmployersService;
+
+impl EmployersService {
+    pub async fn create_employer(
+        _auth_user: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<EmployerBody<EmployerCreateInput>>,
+    ) -> Result<Json<EmployerBody<Employer>>, AppError> {
+        let EmployerBody {
+            employer:
+                EmployerCreateInput {
+                    name,
+                    address,
+                    contact_name,
+                    contact_email,
+                    contact_phone,
+                    billing_email,
+                    billing_address,
+                    tax_id,
+                    hourly_billing_rate,
+                    tax_rate,
+                },
+        } = input;
+
+        Self::validate_name(&name)?;
+        Self::validate_billing(hourly_billing_rate, tax_rate)?;
+
+        let data = prisma
+            .employer()
+            .create(
+                tenant.organization(),
+                name,
+                vec![
+                    employer::address::set(address),
+                    employer::contact_name::set(contact_name),
+                    employer::contact_email::set(contact_email),
+                    employer::contact_phone::set(contact_phone),
+                    employer::billing_email::set(billing_email),
+                    employer::billing_address::set(billing_address),
+                    employer::tax_id::set(tax_id),
+                    employer::hourly_billing_rate::set(hourly_billing_rate),
+                    employer::tax_rate::set(tax_rate.unwrap_or(0.0)),
+                ],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(Json::from(EmployerBody {
+            employer: data.into(),
+        }))
+    }
+
+    pub async fn list_employers(
+        tenant: TenantClient,
+    ) -> Result<Json<EmployersBody<Employer>>, AppError> {
+        let employers = tenant
+            .employers()
+            .find_many(vec![])
+            .order_by(employer::name::order(Direction::Asc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(Employer::from)
+            .collect();
+
+        Ok(Json::from(EmployersBody { employers }))
+    }
+
+    pub async fn get_employer(
+        Path(id): Path<i32>,
+        tenant: TenantClient,
+    ) -> Result<Json<EmployerBody<Employer>>, AppError> {
+        let data = tenant.employers().find_by_id(id).await?;
+
+        Ok(Json::from(EmployerBody {
+            employer: data.into(),
+        }))
+    }
+
+    pub async fn update_employer(
+        Path(id): Path<i32>,
+        _auth_user: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        Json(input): Json<EmployerBody<EmployerUpdateInput>>,
+    ) -> Result<Json<EmployerBody<Employer>>, AppError> {
+        let EmployerBody {
+            employer:
+                EmployerUpdateInput {
+                    name,
+                    address,
+                    contact_name,
+                    contact_email,
+                    contact_phone,
+                    billing_email,
+                    billing_address,
+                    tax_id,
+                    hourly_billing_rate,
+                    tax_rate,
+                },
+        } = input;
+
+        if let Some(name) = &name {
+            Self::validate_name(name)?;
+        }
+        Self::validate_billing(hourly_billing_rate, tax_rate)?;
+
+        let mut params = vec![];
+        if let Some(name) = name {
+            params.push(employer::name::set(name));
+        }
+        if let Some(address) = address {
+            params.push(employer::address::set(Some(address)));
+        }
+        if let Some(contact_name) = contact_name {
+            params.push(employer::contact_name::set(Some(contact_name)));
+        }
+        if let Some(contact_email) = contact_email {
+            params.push(employer::contact_email::set(Some(contact_email)));
+        }
+        if let Some(contact_phone) = contact_phone {
+            params.push(employer::contact_phone::set(Some(contact_phone)));
+        }
+        if let Some(billing_email) = billing_email {
+            params.push(employer::billing_email::set(Some(billing_email)));
+        }
+        if let Some(billing_address) = billing_address {
+            params.push(employer::billing_address::set(Some(billing_address)));
+        }
+        if let Some(tax_id) = tax_id {
+            params.push(employer::tax_id::set(Some(tax_id)));
+        }
+        if let Some(hourly_billing_rate) = hourly_billing_rate {
+            params.push(employer::hourly_billing_rate::set(Some(
+                hourly_billing_rate,
+            )));
+        }
+        if let Some(tax_rate) = tax_rate {
+            params.push(employer::tax_rate::set(tax_rate));
+        }
+
+        let data = tenant.employers().update_by_id(id, params).await?;
+
+        Ok(Json::from(EmployerBody {
+            employer: data.into(),
+        }))
+    }
+
+    pub async fn create_assignment(
+        Path(employer_id): Path<i32>,
+        _auth_user: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<AssignmentBody<AssignmentCreateInput>>,
+    ) -> Result<Json<AssignmentBody<Assignment>>, AppError> {
+        let AssignmentBody {
+            assignment:
+                AssignmentCreateInput {
+                    username,
+                    start_date,
+                    end_date,
+                    rate,
+                },
+        } = input;
+
+        Self::validate_assignment(&start_date, &end_date, rate)?;
+
+        let employer = tenant.employers().find_by_id(employer_id).await?;
+
+        let worker = tenant
+            .users()
+            .find_first(vec![user::username::equals(username)])
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        let data = prisma
+            .assignment()
+            .create(
+                tenant.organization(),
+                user::id::equals(worker.id),
+                employer::id::equals(employer.id),
+                start_date,
+                rate,
+                vec![assignment::end_date::set(end_date)],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(Json::from(AssignmentBody {
+            assignment: data.into(),
+        }))
+    }
+
+    pub async fn list_employer_assignments(
+        Path(employer_id): Path<i32>,
+        _auth_user: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+    ) -> Result<Json<AssignmentsBody<Assignment>>, AppError> {
+        let assignments = tenant
+            .assignments()
+            .find_many(vec![assignment::employer_id::equals(employer_id)])
+            .order_by(assignment::start_date::order(Direction::Desc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(Assignment::from)
+            .collect();
+
+        Ok(Json::from(AssignmentsBody { assignments }))
+    }
+
+    pub async fn list_my_assignments(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+    ) -> Result<Json<AssignmentsBody<Assignment>>, AppError> {
+        let assignments = tenant
+            .assignments()
+            .find_many(vec![assignment::user_id::equals(auth_user.user_id)])
+            .order_by(assignment::start_date::order(Direction::Desc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(Assignment::from)
+            .collect();
+
+        Ok(Json::from(AssignmentsBody { assignments }))
+    }
+
+    pub async fn update_assignment(
+        Path(id): Path<i32>,
+        _auth_user: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+        Json(input): Json<AssignmentBody<AssignmentUpdateInput>>,
+    ) -> Result<Json<AssignmentBody<Assignment>>, AppError> {
+        let AssignmentBody {
+            assignment:
+                AssignmentUpdateInput {
+                    start_date,
+                    end_date,
+                    rate,
+                },
+        } = input;
+
+        let data = tenant.assignments().find_by_id(id).await?;
+
+        Self::validate_assignment(
+            &start_date.unwrap_or(data.start_date),
+            &end_date.or(data.end_date),
+            rate.unwrap_or(data.rate),
+        )?;
+
+        let mut params = vec![];
+        if let Some(start_date) = start_date {
+            params.push(assignment::start_date::set(start_date));
+        }
+        if let Some(end_date) = end_date {
+            params.push(assignment::end_date::set(Some(end_date)));
+        }
+        if let Some(rate) = rate {
+            params.push(assignment::rate::set(rate));
+        }
+
+        let data = tenant.assignments().update_by_id(data.id, params).await?;
+
+        Ok(Json::from(AssignmentBody {
+            assignment: data.into(),
+        }))
+    }
+
+    /// Returns the assignment the user is working under right now, with its
+    /// employer fetched.
+    pub async fn current_assignment(
+        tenant: &TenantClient,
+        user_id: i32,
+    ) -> Result<Option<assignment::Data>, AppError> {
+        let now: chrono::DateTime<chrono::FixedOffset> = chrono::Utc::now().into();
+
+        let data = tenant
+            .assignments()
+            .find_first(vec![
+                assignment::user_id::equals(user_id),
+                assignment::start_date::lte(now),
+                or(vec![
+                    assignment::end_date::equals(None),
+                    assignment::end_date::gte(now),
+                ]),
+            ])
+            .order_by(assignment::start_date::order(Direction::Desc))
+            .with(assignment::employer::fetch())
+            .exec()
+            .await?;
+
+        Ok(data)
+    }
+
+    fn validate_assignment(
+        start_date: &chrono::DateTime<chrono::FixedOffset>,
+        end_date: &Option<chrono::DateTime<chrono::FixedOffset>>,
+        rate: f64,
+    ) -> Result<(), AppError> {
+        if !rate.is_finite() || rate < 0.0 {
+            return Err(AppError::BadRequest(String::from(
+                "Rate must not be negative",
+            )));
+        }
+
+        if let Some(end_date) = end_date {
+            if end_date < start_date {
+                return Err(AppError::BadRequest(String::from(
+                    "End date must not be before start date",
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_name(name: &str) -> Result<(), AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::BadRequest(String::from(
+                "Employer name must not be empty",
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn validate_billing(
+        hourly_billing_rate: Option<f64>,
+        tax_rate: Option<f64>,
+    ) -> Result<(), AppError> {
+        if let Some(rate) = hourly_billing_rate {
+            if !rate.is_finite() || rate < 0.0 {
+                return Err(AppError::BadRequest(String::from(
+                    "Hourly billing rate must not be negative",
+                )));
+            }
+        }
+
+        if let Some(tax_rate) = tax_rate {
+            if !(0.0..=1.0).contains(&tax_rate) {
+                return Err(AppError::BadRequest(String::from(
+                    "Tax rate must be between 0 and 1",
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+pub mod request;
+pub mod response;
+pub mod service;
+pub mod totals;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestTallyBody<T> {
+    pub harvest_tally: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestTalliesBody<T> {
+    pub harvest_tallies: Vec<T>,
+    pub harvest_tallies_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyTotalsBody<T> {
+    pub daily_totals: Vec<T>,
+}
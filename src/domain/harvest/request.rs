@@ -0,0 +1,38 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use crate::prisma::HarvestUnit;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestTallyCreateInput {
+    pub username: String,
+    /// Defaults to the employer of the worker's current assignment.
+    pub employer_id: Option<i32>,
+    pub crop: String,
+    pub unit: HarvestUnit,
+    pub quantity: f64,
+    /// Defaults to now.
+    pub recorded_at: Option<DateTime<FixedOffset>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestTallyListQuery {
+    /// Supervisors may list another worker's tallies, or everyone's when
+    /// omitted. Workers always get their own.
+    pub username: Option<String>,
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyTotalsQuery {
+    pub username: Option<String>,
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+}
@@ -0,0 +1,48 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{harvest_tally, HarvestUnit};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestTally {
+    pub id: i32,
+    pub worker_id: i32,
+    pub employer_id: i32,
+    pub crop: String,
+    pub unit: HarvestUnit,
+    pub quantity: f64,
+    pub recorded_by_id: i32,
+    pub recorded_at: DateTime<FixedOffset>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyTotal {
+    pub date: NaiveDate,
+    pub worker_id: i32,
+    pub username: Option<String>,
+    pub crop: String,
+    pub unit: HarvestUnit,
+    pub quantity: f64,
+    pub tallies_count: i64,
+}
+
+impl From<harvest_tally::Data> for HarvestTally {
+    fn from(data: harvest_tally::Data) -> Self {
+        Self {
+            id: data.id,
+            worker_id: data.worker_id,
+            employer_id: data.employer_id,
+            crop: data.crop,
+            unit: data.unit,
+            quantity: data.quantity,
+            recorded_by_id: data.recorded_by_id,
+            recorded_at: data.recorded_at,
+            notes: data.notes,
+            created_at: data.created_at,
+        }
+    }
+}
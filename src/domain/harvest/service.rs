@@ -0,0 +1,215 @@
+use axum::Extension;
+use prisma_client_rust::{
+    chrono::{self, DateTime, FixedOffset},
+    Direction,
+};
+use std::sync::Arc;
+
+use crate::{
+    app_error::AppError,
+    domain::employers::service::EmployersService,
+    extractor::{roles, AuthUser, Json, Query, RequireRole, RoleRequirement},
+    pagination::{clamp_limit, date_range},
+    prisma::{
+        employer,
+        harvest_tally::{self, WhereParam},
+        user, PrismaClient,
+    },
+    telemetry::Observe,
+    tenant::TenantClient,
+};
+
+use super::{
+    request::{DailyTotalsQuery, HarvestTallyCreateInput, HarvestTallyListQuery},
+    response::{DailyTotal, HarvestTally},
+    totals::{self, Tally},
+    DailyTotalsBody, HarvestTalliesBody, HarvestTallyBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+const DEFAULT_RANGE_DAYS: i64 = 7;
+const MAX_RANGE_DAYS: i64 = 93;
+
+pub struct HarvestService;
+
+impl HarvestService {
+    pub async fn create_tally(
+        supervisor: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<HarvestTallyBody<HarvestTallyCreateInput>>,
+    ) -> Result<Json<HarvestTallyBody<HarvestTally>>, AppError> {
+        let HarvestTallyBody {
+            harvest_tally:
+                HarvestTallyCreateInput {
+                    username,
+                    employer_id,
+                    crop,
+                    unit,
+                    quantity,
+                    recorded_at,
+                    notes,
+                },
+        } = input;
+
+        if crop.trim().is_empty() {
+            return Err(AppError::BadRequest(String::from("Crop must not be empty")));
+        }
+        if !quantity.is_finite() || quantity <= 0.0 {
+            return Err(AppError::BadRequest(String::from(
+                "Quantity must be greater than zero",
+            )));
+        }
+
+        let worker = tenant
+            .users()
+            .find_first(vec![user::username::equals(username)])
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        let employer_id = match employer_id {
+            Some(employer_id) => tenant.employers().find_by_id(employer_id).await?.id,
+            None => EmployersService::current_assignment(&tenant, worker.id)
+                .await?
+                .map(|assignment| assignment.employer_id)
+                .ok_or(AppError::BadRequest(String::from(
+                    "No employer given and the worker has no current assignment",
+                )))?,
+        };
+
+        let data = prisma
+            .harvest_tally()
+            .create(
+                tenant.organization(),
+                user::id::equals(worker.id),
+                employer::id::equals(employer_id),
+                crop,
+                unit,
+                quantity,
+                user::id::equals(supervisor.user_id),
+                recorded_at.unwrap_or_else(|| chrono::Utc::now().into()),
+                vec![harvest_tally::notes::set(notes)],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(Json::from(HarvestTallyBody {
+            harvest_tally: data.into(),
+        }))
+    }
+
+    pub async fn list_tallies(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        Query(query): Query<HarvestTallyListQuery>,
+    ) -> Result<Json<HarvestTalliesBody<HarvestTally>>, AppError> {
+        let worker_id = Self::visible_worker(&tenant, &auth_user, query.username.clone()).await?;
+
+        let harvest_tallies_count = tenant
+            .harvest_tallies()
+            .count(Self::list_filters(worker_id, query.from, query.to))
+            .exec()
+            .await?;
+
+        let harvest_tallies = tenant
+            .harvest_tallies()
+            .find_many(Self::list_filters(worker_id, query.from, query.to))
+            .order_by(harvest_tally::recorded_at::order(Direction::Desc))
+            .skip(query.offset.unwrap_or(0).max(0))
+            .take(clamp_limit(query.limit))
+            .exec()
+            .await?
+            .into_iter()
+            .map(HarvestTally::from)
+            .collect();
+
+        Ok(Json::from(HarvestTalliesBody {
+            harvest_tallies,
+            harvest_tallies_count,
+        }))
+    }
+
+    pub async fn list_daily_totals(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        Query(query): Query<DailyTotalsQuery>,
+    ) -> Result<Json<DailyTotalsBody<DailyTotal>>, AppError> {
+        let worker_id = Self::visible_worker(&tenant, &auth_user, query.username).await?;
+        let (from, to) = date_range(query.from, query.to, DEFAULT_RANGE_DAYS, MAX_RANGE_DAYS)?;
+
+        let tallies = tenant
+            .harvest_tallies()
+            .find_many(Self::list_filters(worker_id, Some(from), Some(to)))
+            .with(harvest_tally::worker::fetch())
+            .exec()
+            .await?;
+
+        Ok(Json::from(DailyTotalsBody {
+            daily_totals: Self::daily_totals(&tallies),
+        }))
+    }
+
+    /// See [`totals::daily_totals`].
+    pub fn daily_totals(tallies: &[harvest_tally::Data]) -> Vec<DailyTotal> {
+        totals::daily_totals(tallies.iter().map(|tally| Tally {
+            worker_id: tally.worker_id,
+            username: tally.worker().ok().map(|worker| worker.username.clone()),
+            crop: tally.crop.clone(),
+            unit: tally.unit,
+            quantity: tally.quantity,
+            recorded_at: tally.recorded_at,
+        }))
+    }
+
+    /// Whose tallies the caller may see: themselves, or for supervisors the
+    /// named worker or everyone (`None`).
+    async fn visible_worker(
+        tenant: &TenantClient,
+        auth_user: &AuthUser,
+        username: Option<String>,
+    ) -> Result<Option<i32>, AppError> {
+        let is_supervisor = auth_user.has_role(roles::Supervisor::ALLOWED);
+
+        match username {
+            Some(username) => {
+                let worker = tenant
+                    .users()
+                    .find_first(vec![user::username::equals(username)])
+                    .exec()
+                    .await?
+                    .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+                if worker.id != auth_user.user_id && !is_supervisor {
+                    return Err(AppError::Forbidden(String::from(
+                        "Only supervisors can view other workers' tallies",
+                    )));
+                }
+
+                Ok(Some(worker.id))
+            }
+            None if is_supervisor => Ok(None),
+            None => Ok(Some(auth_user.user_id)),
+        }
+    }
+
+    fn list_filters(
+        worker_id: Option<i32>,
+        from: Option<DateTime<FixedOffset>>,
+        to: Option<DateTime<FixedOffset>>,
+    ) -> Vec<WhereParam> {
+        let mut filters = vec![];
+        if let Some(worker_id) = worker_id {
+            filters.push(harvest_tally::worker_id::equals(worker_id));
+        }
+        if let Some(from) = from {
+            filters.push(harvest_tally::recorded_at::gte(from));
+        }
+        if let Some(to) = to {
+            filters.push(harvest_tally::recorded_at::lt(to));
+        }
+        filters
+    }
+}
@@ -0,0 +1,51 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use std::collections::HashMap;
+
+use crate::prisma::HarvestUnit;
+
+use super::response::DailyTotal;
+
+/// A harvest tally as far as daily totals are concerned.
+#[derive(Debug, Clone)]
+pub struct Tally {
+    pub worker_id: i32,
+    pub username: Option<String>,
+    pub crop: String,
+    pub unit: HarvestUnit,
+    pub quantity: f64,
+    pub recorded_at: DateTime<FixedOffset>,
+}
+
+/// Sums tallies per UTC day, worker, crop and unit, sorted in that order.
+/// Quantities in different units are never added together.
+pub fn daily_totals(tallies: impl IntoIterator<Item = Tally>) -> Vec<DailyTotal> {
+    let mut totals: HashMap<_, DailyTotal> = HashMap::new();
+
+    for tally in tallies {
+        let date = tally.recorded_at.naive_utc().date();
+        let key = (date, tally.worker_id, tally.crop.clone(), tally.unit);
+
+        let total = totals.entry(key).or_insert_with(|| DailyTotal {
+            date,
+            worker_id: tally.worker_id,
+            username: tally.username.clone(),
+            crop: tally.crop.clone(),
+            unit: tally.unit,
+            quantity: 0.0,
+            tallies_count: 0,
+        });
+        total.quantity += tally.quantity;
+        total.tallies_count += 1;
+    }
+
+    let mut totals: Vec<DailyTotal> = totals.into_values().collect();
+    totals.sort_by_cached_key(|total| {
+        (
+            total.date,
+            total.worker_id,
+            total.crop.clone(),
+            total.unit as u8,
+        )
+    });
+    totals
+}
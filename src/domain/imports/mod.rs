@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+pub mod parser;
+pub mod request;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReportBody<T> {
+    pub import_report: T,
+}
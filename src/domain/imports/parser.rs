@@ -0,0 +1,295 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::validation::rules;
+
+/// Files larger than this are rejected as a whole.
+pub const MAX_ROWS: usize = 5_000;
+
+/// Columns a users file must have. `first_name`, `last_name` and `email`
+/// are optional.
+pub const USER_COLUMNS: [&str; 1] = ["username"];
+/// Columns a work entries file must have. `break_minutes` and `notes` are
+/// optional.
+pub const WORK_LOG_COLUMNS: [&str; 5] =
+    ["username", "employer", "job_type", "start_time", "end_time"];
+
+/// A problem with one row. `row` is the line number in the file, counting
+/// the header as line 1.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowError {
+    pub row: usize,
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserRow {
+    pub row: usize,
+    pub username: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkLogRow {
+    pub row: usize,
+    pub username: String,
+    pub employer: String,
+    pub job_type: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub break_minutes: i32,
+    pub notes: Option<String>,
+}
+
+/// Rows that passed validation, and every problem found in the others.
+#[derive(Debug)]
+pub struct Parsed<T> {
+    pub rows: Vec<T>,
+    pub errors: Vec<RowError>,
+    pub total: usize,
+}
+
+// Derived, this would require `T: Default`, which the rows are not
+impl<T> Default for Parsed<T> {
+    fn default() -> Self {
+        Self {
+            rows: vec![],
+            errors: vec![],
+            total: 0,
+        }
+    }
+}
+
+impl RowError {
+    pub fn new(row: usize, field: &str, message: &str) -> Self {
+        Self {
+            row,
+            field: String::from(field),
+            message: String::from(message),
+        }
+    }
+}
+
+/// Parses a users file. Usernames and emails must also be unique within the
+/// file; clashes with existing accounts are checked against the database
+/// later.
+pub fn parse_users(input: &[u8]) -> Parsed<UserRow> {
+    let mut parsed = Parsed::default();
+    let mut usernames = HashSet::new();
+    let mut emails = HashSet::new();
+
+    for_each_record(input, &USER_COLUMNS, &mut parsed, |row, record, errors| {
+        let username = record.required("username", row, errors)?;
+        errors_from(row, "username", rules::username(&username), errors);
+
+        let email = record.optional("email");
+        if let Some(email) = &email {
+            errors_from(row, "email", rules::email(email), errors);
+            if !emails.insert(email.to_lowercase()) {
+                errors.push(RowError::new(row, "email", "Duplicate email in file"));
+            }
+        }
+        if !usernames.insert(username.to_lowercase()) {
+            errors.push(RowError::new(row, "username", "Duplicate username in file"));
+        }
+
+        Some(UserRow {
+            row,
+            username,
+            first_name: record.optional("first_name"),
+            last_name: record.optional("last_name"),
+            email,
+        })
+    });
+
+    parsed
+}
+
+/// Parses a file of historical work entries. Entries must be closed, with
+/// times in RFC 3339.
+pub fn parse_work_logs(input: &[u8]) -> Parsed<WorkLogRow> {
+    let mut parsed = Parsed::default();
+
+    for_each_record(
+        input,
+        &WORK_LOG_COLUMNS,
+        &mut parsed,
+        |row, record, errors| {
+            let username = record.required("username", row, errors);
+            let employer = record.required("employer", row, errors);
+            let job_type = record.required("job_type", row, errors);
+            let start_time = record.time("start_time", row, errors);
+            let end_time = record.time("end_time", row, errors);
+
+            let break_minutes = match record.optional("break_minutes") {
+                None => Some(0),
+                Some(value) => match value.parse::<i32>() {
+                    Ok(minutes) if minutes >= 0 => Some(minutes),
+                    _ => {
+                        errors.push(RowError::new(
+                            row,
+                            "break_minutes",
+                            "Must be a whole number of minutes, not negative",
+                        ));
+                        None
+                    }
+                },
+            };
+
+            let (start_time, end_time) = (start_time?, end_time?);
+            if end_time <= start_time {
+                errors.push(RowError::new(
+                    row,
+                    "end_time",
+                    "Must be after the start time",
+                ));
+                return None;
+            }
+            let break_minutes = break_minutes?;
+            if let Err(message) = rules::break_fits_shift(&start_time, &end_time, break_minutes) {
+                errors.push(RowError::new(row, "break_minutes", message));
+                return None;
+            }
+
+            Some(WorkLogRow {
+                row,
+                username: username?,
+                employer: employer?,
+                job_type: job_type?,
+                start_time,
+                end_time,
+                break_minutes,
+                notes: record.optional("notes"),
+            })
+        },
+    );
+
+    parsed
+}
+
+/// One CSV record with its fields looked up by header name.
+struct Record<'a> {
+    columns: &'a HashMap<String, usize>,
+    record: csv::StringRecord,
+}
+
+impl Record<'_> {
+    fn optional(&self, column: &str) -> Option<String> {
+        self.columns
+            .get(column)
+            .and_then(|index| self.record.get(*index))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+    }
+
+    fn required(&self, column: &str, row: usize, errors: &mut Vec<RowError>) -> Option<String> {
+        let value = self.optional(column);
+        if value.is_none() {
+            errors.push(RowError::new(row, column, "Must not be empty"));
+        }
+        value
+    }
+
+    fn time(
+        &self,
+        column: &str,
+        row: usize,
+        errors: &mut Vec<RowError>,
+    ) -> Option<DateTime<FixedOffset>> {
+        let value = self.required(column, row, errors)?;
+        match DateTime::parse_from_rfc3339(&value) {
+            Ok(time) => Some(time),
+            Err(_) => {
+                errors.push(RowError::new(
+                    row,
+                    column,
+                    "Must be an RFC 3339 time, e.g. 2024-04-01T06:00:00Z",
+                ));
+                None
+            }
+        }
+    }
+}
+
+/// Reads the header, then hands every record to `parse`. A row is kept only
+/// if `parse` returns it without adding errors.
+fn for_each_record<T, F>(input: &[u8], required: &[&str], parsed: &mut Parsed<T>, mut parse: F)
+where
+    F: FnMut(usize, &Record, &mut Vec<RowError>) -> Option<T>,
+{
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input);
+
+    let columns: HashMap<String, usize> = match reader.headers() {
+        Ok(headers) => headers
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.trim().to_lowercase(), index))
+            .collect(),
+        Err(_) => {
+            parsed
+                .errors
+                .push(RowError::new(1, "header", "Could not read the header row"));
+            return;
+        }
+    };
+
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|column| !columns.contains_key(**column))
+        .copied()
+        .collect();
+    if !missing.is_empty() {
+        parsed.errors.push(RowError::new(
+            1,
+            "header",
+            &format!("Missing columns: {}", missing.join(", ")),
+        ));
+        return;
+    }
+
+    for (index, result) in reader.records().enumerate() {
+        let row = index + 2;
+        parsed.total += 1;
+
+        if parsed.total > MAX_ROWS {
+            parsed.errors.push(RowError::new(
+                row,
+                "row",
+                &format!("Files may contain at most {} rows", MAX_ROWS),
+            ));
+            return;
+        }
+
+        let record = match result {
+            Ok(record) => Record {
+                columns: &columns,
+                record,
+            },
+            Err(_) => {
+                parsed
+                    .errors
+                    .push(RowError::new(row, "row", "Could not parse this line"));
+                continue;
+            }
+        };
+
+        let mut errors = vec![];
+        let value = parse(row, &record, &mut errors);
+        match value {
+            Some(value) if errors.is_empty() => parsed.rows.push(value),
+            _ => parsed.errors.extend(errors),
+        }
+    }
+}
+
+fn errors_from(row: usize, field: &str, result: Result<(), &str>, errors: &mut Vec<RowError>) {
+    if let Err(message) = result {
+        errors.push(RowError::new(row, field, message));
+    }
+}
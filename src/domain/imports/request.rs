@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportQuery {
+    /// Without it the import is a dry run that only reports problems.
+    pub commit: Option<bool>,
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+
+use crate::app_error::{AppError, FieldError};
+
+use super::parser::RowError;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub committed: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub errors: Vec<RowError>,
+    /// Initial passwords of imported users. Only shown once, after a commit.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub credentials: Vec<Credential>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Credential {
+    pub username: String,
+    pub password: String,
+}
+
+impl ImportReport {
+    pub fn new(total_rows: usize, valid_rows: usize, mut errors: Vec<RowError>) -> Self {
+        errors.sort_by_key(|error| error.row);
+
+        Self {
+            committed: false,
+            total_rows,
+            valid_rows,
+            errors,
+            credentials: vec![],
+        }
+    }
+
+    /// The row errors as a 422, for a commit that was refused.
+    pub fn validation_error(&self) -> AppError {
+        AppError::Validation(
+            self.errors
+                .iter()
+                .map(|error| {
+                    FieldError::new(
+                        &format!("rows.{}.{}", error.row, error.field),
+                        &error.message,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,328 @@
+use axum::{body::Bytes, Extension};
+use prisma_client_rust::{
+    chrono::{self, DateTime, FixedOffset},
+    prisma_errors::query_engine::UniqueKeyViolation,
+    QueryError,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    app_error::AppError,
+    domain::users::service::UsersService,
+    extractor::{roles, Json, Query, RequireRole},
+    prisma::{employer, organization, user, work_log, PrismaClient, WorkLogStatus},
+    telemetry::{self, Observe},
+    tenant::TenantClient,
+};
+
+use super::{
+    parser::{self, RowError, UserRow},
+    request::ImportQuery,
+    response::{Credential, ImportReport},
+    ImportReportBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+pub struct ImportsService;
+
+impl ImportsService {
+    pub async fn import_users(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Query(query): Query<ImportQuery>,
+        body: Bytes,
+    ) -> Result<Json<ImportReportBody<ImportReport>>, AppError> {
+        let commit = query.commit.unwrap_or(false);
+        let report = Self::run_user_import(&prisma, &tenant, &body, commit).await?;
+
+        Self::respond(report, commit)
+    }
+
+    pub async fn import_work_logs(
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Query(query): Query<ImportQuery>,
+        body: Bytes,
+    ) -> Result<Json<ImportReportBody<ImportReport>>, AppError> {
+        let commit = query.commit.unwrap_or(false);
+        let report =
+            Self::run_work_log_import(&prisma, &tenant, Some(admin.user_id), &body, commit).await?;
+
+        Self::respond(report, commit)
+    }
+
+    /// Validates a users file and, when `commit` is set and every row is
+    /// valid, creates the users as workers of the organization with
+    /// generated passwords. Either every user is created or none is.
+    pub async fn run_user_import(
+        prisma: &PrismaClient,
+        tenant: &TenantClient,
+        input: &[u8],
+        commit: bool,
+    ) -> Result<ImportReport, AppError> {
+        let parsed = parser::parse_users(input);
+        let mut errors = parsed.errors;
+
+        let usernames: Vec<String> = parsed.rows.iter().map(|row| row.username.clone()).collect();
+        let emails: Vec<String> = parsed
+            .rows
+            .iter()
+            .filter_map(|row| row.email.clone())
+            .collect();
+
+        // Usernames and emails are compared case-insensitively, like the
+        // database collation does
+        let taken_usernames: HashSet<String> = prisma
+            .user()
+            .find_many(vec![user::username::in_vec(usernames)])
+            .observed()
+            .exec()
+            .await?
+            .into_iter()
+            .map(|user| user.username.to_lowercase())
+            .collect();
+        let taken_emails: HashSet<String> = prisma
+            .user()
+            .find_many(vec![user::email::in_vec(emails)])
+            .observed()
+            .exec()
+            .await?
+            .into_iter()
+            .filter_map(|user| user.email.map(|email| email.to_lowercase()))
+            .collect();
+
+        let mut rows = vec![];
+        for row in parsed.rows {
+            let mut valid = true;
+            if taken_usernames.contains(&row.username.to_lowercase()) {
+                errors.push(RowError::new(
+                    row.row,
+                    "username",
+                    "Username is already taken",
+                ));
+                valid = false;
+            }
+            if let Some(email) = &row.email {
+                if taken_emails.contains(&email.to_lowercase()) {
+                    errors.push(RowError::new(row.row, "email", "Email is already in use"));
+                    valid = false;
+                }
+            }
+            if valid {
+                rows.push(row);
+            }
+        }
+
+        let mut report = ImportReport::new(parsed.total, rows.len(), errors);
+        if !commit || !report.errors.is_empty() || rows.is_empty() {
+            return Ok(report);
+        }
+
+        // Hashing hundreds of passwords would stall the async workers
+        let users = tokio::task::spawn_blocking(move || {
+            rows.into_iter()
+                .map(|row| {
+                    let password = UsersService::generate_password();
+                    let hash = UsersService::hash_password(&password)?;
+                    Ok((row, password, hash))
+                })
+                .collect::<anyhow::Result<Vec<(UserRow, String, String)>>>()
+        })
+        .await
+        .map_err(anyhow::Error::new)??;
+
+        let org_id = tenant.org_id();
+        let credentials = telemetry::observe_query(
+            "transaction.run_user_import",
+            prisma._transaction().run(|tx| async move {
+                let mut credentials = vec![];
+                for (row, password, hash) in users {
+                    let data = tx
+                        .user()
+                        .create(
+                            hash,
+                            row.username,
+                            vec![
+                                user::first_name::set(row.first_name),
+                                user::last_name::set(row.last_name),
+                                user::email::set(row.email),
+                            ],
+                        )
+                        .observed()
+                        .exec()
+                        .await?;
+
+                    tx.membership()
+                        .create(
+                            organization::id::equals(org_id),
+                            user::id::equals(data.id),
+                            vec![],
+                        )
+                        .observed()
+                        .exec()
+                        .await?;
+
+                    credentials.push(Credential {
+                        username: data.username,
+                        password,
+                    });
+                }
+
+                Ok::<_, QueryError>(credentials)
+            }),
+        )
+        .await
+        .map_err(|e| {
+            if e.is_prisma_error::<UniqueKeyViolation>() {
+                AppError::Conflict(String::from(
+                    "A username or email was taken during the import, please retry",
+                ))
+            } else {
+                e.into()
+            }
+        })?;
+
+        report.committed = true;
+        report.credentials = credentials;
+
+        Ok(report)
+    }
+
+    /// Validates a file of historical work entries and, when `commit` is set
+    /// and every row is valid, stores them in one transaction. Imported
+    /// entries are already approved, by `reviewer_id` if given.
+    pub async fn run_work_log_import(
+        prisma: &PrismaClient,
+        tenant: &TenantClient,
+        reviewer_id: Option<i32>,
+        input: &[u8],
+        commit: bool,
+    ) -> Result<ImportReport, AppError> {
+        let parsed = parser::parse_work_logs(input);
+        let mut errors = parsed.errors;
+
+        let usernames: Vec<String> = parsed.rows.iter().map(|row| row.username.clone()).collect();
+        let workers: HashMap<String, i32> = tenant
+            .users()
+            .find_many(vec![user::username::in_vec(usernames)])
+            .exec()
+            .await?
+            .into_iter()
+            .map(|user| (user.username.to_lowercase(), user.id))
+            .collect();
+
+        let mut employers: HashMap<String, Vec<i32>> = HashMap::new();
+        for employer in tenant.employers().find_many(vec![]).exec().await? {
+            employers
+                .entry(employer.name.trim().to_lowercase())
+                .or_default()
+                .push(employer.id);
+        }
+
+        let mut rows = vec![];
+        for row in parsed.rows {
+            let worker_id = workers.get(&row.username.to_lowercase()).copied();
+            if worker_id.is_none() {
+                errors.push(RowError::new(
+                    row.row,
+                    "username",
+                    "No member of the organization has this username",
+                ));
+            }
+
+            let employer_id = match employers
+                .get(&row.employer.to_lowercase())
+                .map(Vec::as_slice)
+            {
+                Some([employer_id]) => Some(*employer_id),
+                Some(_) => {
+                    errors.push(RowError::new(
+                        row.row,
+                        "employer",
+                        "Several employers have this name",
+                    ));
+                    None
+                }
+                None => {
+                    errors.push(RowError::new(
+                        row.row,
+                        "employer",
+                        "No employer has this name",
+                    ));
+                    None
+                }
+            };
+
+            if let (Some(worker_id), Some(employer_id)) = (worker_id, employer_id) {
+                rows.push((row, worker_id, employer_id));
+            }
+        }
+
+        let mut report = ImportReport::new(parsed.total, rows.len(), errors);
+        if !commit || !report.errors.is_empty() || rows.is_empty() {
+            return Ok(report);
+        }
+
+        let org_id = tenant.org_id();
+        let reviewed_at: DateTime<FixedOffset> = chrono::Utc::now().into();
+
+        telemetry::observe_query(
+            "transaction.run_work_log_import",
+            prisma._transaction().run(|tx| async move {
+                for (row, worker_id, employer_id) in rows {
+                    let mut params = vec![
+                        work_log::end_time::set(Some(row.end_time)),
+                        work_log::break_minutes::set(row.break_minutes),
+                        work_log::notes::set(row.notes),
+                        work_log::status::set(WorkLogStatus::Approved),
+                        work_log::reviewed_at::set(Some(reviewed_at)),
+                    ];
+                    if let Some(reviewer_id) = reviewer_id {
+                        params.push(work_log::reviewed_by::connect(user::id::equals(
+                            reviewer_id,
+                        )));
+                    }
+
+                    tx.work_log()
+                        .create(
+                            organization::id::equals(org_id),
+                            user::id::equals(worker_id),
+                            employer::id::equals(employer_id),
+                            row.job_type,
+                            row.start_time,
+                            params,
+                        )
+                        .observed()
+                        .exec()
+                        .await?;
+                }
+
+                Ok::<_, QueryError>(())
+            }),
+        )
+        .await?;
+
+        report.committed = true;
+
+        Ok(report)
+    }
+
+    fn respond(
+        report: ImportReport,
+        commit: bool,
+    ) -> Result<Json<ImportReportBody<ImportReport>>, AppError> {
+        if commit && !report.errors.is_empty() {
+            return Err(report.validation_error());
+        }
+
+        Ok(Json::from(ImportReportBody {
+            import_report: report,
+        }))
+    }
+}
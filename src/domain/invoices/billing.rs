@@ -0,0 +1,68 @@
+use crate::domain::payroll::calculator::round_cents;
+
+/// A work log or harvest tally priced for an invoice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BillableLine {
+    pub work_log_id: Option<i32>,
+    pub harvest_tally_id: Option<i32>,
+    pub description: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+/// Totals derived from the line amounts and the employer's tax rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvoiceTotals {
+    pub subtotal: f64,
+    pub tax: f64,
+    pub total: f64,
+}
+
+impl BillableLine {
+    /// Bills a shift by the hour. The quantity is the exact number of hours
+    /// so that `quantity * unit_price` always rounds to the stored amount.
+    pub fn hours(work_log_id: i32, description: String, worked_minutes: i64, rate: f64) -> Self {
+        Self::new(
+            Some(work_log_id),
+            None,
+            description,
+            worked_minutes as f64 / 60.0,
+            rate,
+        )
+    }
+
+    pub fn pieces(harvest_tally_id: i32, description: String, quantity: f64, rate: f64) -> Self {
+        Self::new(None, Some(harvest_tally_id), description, quantity, rate)
+    }
+
+    fn new(
+        work_log_id: Option<i32>,
+        harvest_tally_id: Option<i32>,
+        description: String,
+        quantity: f64,
+        unit_price: f64,
+    ) -> Self {
+        Self {
+            work_log_id,
+            harvest_tally_id,
+            description,
+            quantity,
+            unit_price,
+            amount: round_cents(quantity * unit_price),
+        }
+    }
+}
+
+/// Sums the line amounts and applies tax once on the subtotal, so totals do
+/// not drift with the number of lines.
+pub fn totals(amounts: impl IntoIterator<Item = f64>, tax_rate: f64) -> InvoiceTotals {
+    let subtotal = round_cents(amounts.into_iter().sum());
+    let tax = round_cents(subtotal * tax_rate);
+
+    InvoiceTotals {
+        subtotal,
+        tax,
+        total: round_cents(subtotal + tax),
+    }
+}
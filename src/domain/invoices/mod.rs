@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+pub mod billing;
+pub mod request;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceBody<T> {
+    pub invoice: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoicesBody<T> {
+    pub invoices: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationBody<T> {
+    pub reconciliation: T,
+}
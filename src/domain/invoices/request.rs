@@ -0,0 +1,19 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use crate::prisma::InvoiceStatus;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceCreateInput {
+    pub employer_id: i32,
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceListQuery {
+    pub employer_id: Option<i32>,
+    pub status: Option<InvoiceStatus>,
+}
@@ -0,0 +1,107 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{invoice, invoice_line, InvoiceStatus};
+
+use super::billing::InvoiceTotals;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceLine {
+    pub id: i32,
+    pub work_log_id: Option<i32>,
+    pub harvest_tally_id: Option<i32>,
+    pub description: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    pub id: i32,
+    pub number: Option<i32>,
+    pub employer_id: i32,
+    pub status: InvoiceStatus,
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+    pub tax_rate: f64,
+    pub subtotal: f64,
+    pub tax: f64,
+    pub total: f64,
+    pub issued_at: Option<DateTime<FixedOffset>>,
+    pub paid_at: Option<DateTime<FixedOffset>>,
+    pub voided_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<InvoiceLine>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    pub subtotal: f64,
+    pub tax: f64,
+    pub total: f64,
+}
+
+/// Stored totals next to the ones recomputed from the linked work records.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reconciliation {
+    pub invoice_id: i32,
+    pub stored: Totals,
+    pub recomputed: Totals,
+    pub matches: bool,
+    /// Lines whose work record changed or no longer exists.
+    pub changed_line_ids: Vec<i32>,
+}
+
+impl From<InvoiceTotals> for Totals {
+    fn from(totals: InvoiceTotals) -> Self {
+        Self {
+            subtotal: totals.subtotal,
+            tax: totals.tax,
+            total: totals.total,
+        }
+    }
+}
+
+impl From<invoice_line::Data> for InvoiceLine {
+    fn from(data: invoice_line::Data) -> Self {
+        Self {
+            id: data.id,
+            work_log_id: data.work_log_id,
+            harvest_tally_id: data.harvest_tally_id,
+            description: data.description,
+            quantity: data.quantity,
+            unit_price: data.unit_price,
+            amount: data.amount,
+        }
+    }
+}
+
+impl From<invoice::Data> for Invoice {
+    fn from(data: invoice::Data) -> Self {
+        Self {
+            lines: data
+                .lines
+                .map(|lines| lines.into_iter().map(InvoiceLine::from).collect()),
+            id: data.id,
+            number: data.number,
+            employer_id: data.employer_id,
+            status: data.status,
+            period_start: data.period_start,
+            period_end: data.period_end,
+            tax_rate: data.tax_rate,
+            subtotal: data.subtotal,
+            tax: data.tax,
+            total: data.total,
+            issued_at: data.issued_at,
+            paid_at: data.paid_at,
+            voided_at: data.voided_at,
+            created_at: data.created_at,
+        }
+    }
+}
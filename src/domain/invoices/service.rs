@@ -0,0 +1,536 @@
+use axum::Extension;
+use prisma_client_rust::{
+    chrono::{self, DateTime, Duration, FixedOffset},
+    operator::or,
+    Direction,
+};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    app_error::AppError,
+    extractor::{roles, Json, Path, Query, RequireRole},
+    prisma::{
+        employer, harvest_tally, invoice, invoice_line, organization, piece_rate, work_log,
+        HarvestUnit, InvoiceStatus, PrismaClient, WorkLogStatus,
+    },
+    telemetry::{self, Observe},
+    tenant::TenantClient,
+    validation::ValidationErrors,
+};
+
+use super::{
+    billing::{self, BillableLine},
+    request::{InvoiceCreateInput, InvoiceListQuery},
+    response::{Invoice, Reconciliation},
+    InvoiceBody, InvoicesBody, ReconciliationBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+const MAX_PERIOD_DAYS: i64 = 93;
+
+/// Invoices that still bill their records. Records on a void invoice can be
+/// billed again.
+const BILLING_STATUSES: [InvoiceStatus; 3] = [
+    InvoiceStatus::Draft,
+    InvoiceStatus::Issued,
+    InvoiceStatus::Paid,
+];
+
+pub struct InvoicesService;
+
+impl InvoicesService {
+    /// Drafts an invoice for every unbilled approved work log and harvest
+    /// tally of the employer in `[period_start, period_end)`, priced at the
+    /// employer's current billing rates.
+    pub async fn create_invoice(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<InvoiceBody<InvoiceCreateInput>>,
+    ) -> Result<Json<InvoiceBody<Invoice>>, AppError> {
+        let InvoiceBody {
+            invoice:
+                InvoiceCreateInput {
+                    employer_id,
+                    period_start,
+                    period_end,
+                },
+        } = input;
+
+        let mut errors = ValidationErrors::default();
+        if period_end <= period_start {
+            errors.add("periodEnd", "Must be after the period start");
+        } else if period_end - period_start > Duration::days(MAX_PERIOD_DAYS) {
+            errors.add("periodEnd", "An invoice may span at most 93 days");
+        }
+        errors.into_result()?;
+
+        let employer = tenant.employers().find_by_id(employer_id).await?;
+        let lines = Self::price(&tenant, &employer, period_start, period_end).await?;
+        if lines.is_empty() {
+            return Err(AppError::BadRequest(String::from(
+                "No unbilled work for this employer in the period",
+            )));
+        }
+
+        let totals = billing::totals(lines.iter().map(|line| line.amount), employer.tax_rate);
+        let org_id = tenant.org_id();
+
+        let invoice_id = telemetry::observe_query(
+            "transaction.create_invoice",
+            prisma._transaction().run(|tx| async move {
+                let invoice = tx
+                    .invoice()
+                    .create(
+                        organization::id::equals(org_id),
+                        employer::id::equals(employer.id),
+                        period_start,
+                        period_end,
+                        employer.tax_rate,
+                        totals.subtotal,
+                        totals.tax,
+                        totals.total,
+                        vec![],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+
+                for line in lines {
+                    tx.invoice_line()
+                        .create(
+                            invoice::id::equals(invoice.id),
+                            line.description,
+                            line.quantity,
+                            line.unit_price,
+                            line.amount,
+                            vec![
+                                invoice_line::work_log_id::set(line.work_log_id),
+                                invoice_line::harvest_tally_id::set(line.harvest_tally_id),
+                            ],
+                        )
+                        .observed()
+                        .exec()
+                        .await?;
+                }
+
+                Ok::<_, AppError>(invoice.id)
+            }),
+        )
+        .await?;
+
+        let data = Self::find_invoice(&tenant, invoice_id).await?;
+
+        Ok(Json::from(InvoiceBody {
+            invoice: data.into(),
+        }))
+    }
+
+    pub async fn list_invoices(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        Query(query): Query<InvoiceListQuery>,
+    ) -> Result<Json<InvoicesBody<Invoice>>, AppError> {
+        let mut filters = vec![];
+        if let Some(employer_id) = query.employer_id {
+            filters.push(invoice::employer_id::equals(employer_id));
+        }
+        if let Some(status) = query.status {
+            filters.push(invoice::status::equals(status));
+        }
+
+        let invoices = tenant
+            .invoices()
+            .find_many(filters)
+            .order_by(invoice::created_at::order(Direction::Desc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(Invoice::from)
+            .collect();
+
+        Ok(Json::from(InvoicesBody { invoices }))
+    }
+
+    pub async fn get_invoice(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<InvoiceBody<Invoice>>, AppError> {
+        let data = Self::find_invoice(&tenant, id).await?;
+
+        Ok(Json::from(InvoiceBody {
+            invoice: data.into(),
+        }))
+    }
+
+    /// Assigns the next number of the organization and freezes the invoice.
+    /// Numbers are taken inside the transaction that issues the invoice, so
+    /// a failed issue leaves no gap in the sequence.
+    pub async fn issue_invoice(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<InvoiceBody<Invoice>>, AppError> {
+        let invoice = tenant.invoices().find_by_id(id).await?;
+        if invoice.status != InvoiceStatus::Draft {
+            return Err(AppError::Conflict(String::from(
+                "Only draft invoices can be issued",
+            )));
+        }
+
+        let now = Self::now();
+
+        telemetry::observe_query(
+            "transaction.issue_invoice",
+            prisma._transaction().run(|tx| async move {
+                // Another draft may bill the same records; only one of them
+                // can be issued
+                let double_billed = tx
+                    .invoice_line()
+                    .count(vec![
+                        invoice_line::invoice_id::equals(invoice.id),
+                        or(vec![
+                            invoice_line::work_log::is(vec![work_log::invoice_lines::some(
+                                Self::billed_elsewhere(invoice.id),
+                            )]),
+                            invoice_line::harvest_tally::is(vec![
+                                harvest_tally::invoice_lines::some(Self::billed_elsewhere(
+                                    invoice.id,
+                                )),
+                            ]),
+                        ]),
+                    ])
+                    .observed()
+                    .exec()
+                    .await?;
+                if double_billed > 0 {
+                    return Err(AppError::Conflict(String::from(
+                        "Some records are already on an issued invoice",
+                    )));
+                }
+
+                // The increment locks the organization row until commit,
+                // which serializes concurrent issues
+                let organization = tx
+                    .organization()
+                    .update(
+                        organization::id::equals(invoice.organization_id),
+                        vec![organization::next_invoice_number::increment(1)],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+                let number = organization.next_invoice_number - 1;
+
+                let issued = tx
+                    .invoice()
+                    .update_many(
+                        vec![
+                            invoice::id::equals(invoice.id),
+                            invoice::status::equals(InvoiceStatus::Draft),
+                        ],
+                        vec![
+                            invoice::status::set(InvoiceStatus::Issued),
+                            invoice::number::set(Some(number)),
+                            invoice::issued_at::set(Some(now)),
+                        ],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+                if issued == 0 {
+                    return Err(AppError::Conflict(String::from(
+                        "Only draft invoices can be issued",
+                    )));
+                }
+
+                Ok(())
+            }),
+        )
+        .await?;
+
+        let data = Self::find_invoice(&tenant, id).await?;
+
+        Ok(Json::from(InvoiceBody {
+            invoice: data.into(),
+        }))
+    }
+
+    pub async fn pay_invoice(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<InvoiceBody<Invoice>>, AppError> {
+        Self::transition(
+            &tenant,
+            id,
+            vec![InvoiceStatus::Issued],
+            vec![
+                invoice::status::set(InvoiceStatus::Paid),
+                invoice::paid_at::set(Some(Self::now())),
+            ],
+            "Only issued invoices can be marked as paid",
+        )
+        .await
+    }
+
+    /// Voided invoices keep their number and lines, but their records can be
+    /// billed again.
+    pub async fn void_invoice(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<InvoiceBody<Invoice>>, AppError> {
+        Self::transition(
+            &tenant,
+            id,
+            vec![InvoiceStatus::Draft, InvoiceStatus::Issued],
+            vec![
+                invoice::status::set(InvoiceStatus::Void),
+                invoice::voided_at::set(Some(Self::now())),
+            ],
+            "Paid or void invoices cannot be voided",
+        )
+        .await
+    }
+
+    /// Recomputes every line from its work record at the stored unit price
+    /// and compares the totals with the stored ones.
+    pub async fn reconcile_invoice(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<ReconciliationBody<Reconciliation>>, AppError> {
+        let data = tenant
+            .invoices()
+            .find_first(vec![invoice::id::equals(id)])
+            .with(
+                invoice::lines::fetch(vec![])
+                    .with(invoice_line::work_log::fetch())
+                    .with(invoice_line::harvest_tally::fetch()),
+            )
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("Invoice not found")))?;
+
+        let mut amounts = vec![];
+        let mut changed_line_ids = vec![];
+        for line in data.lines.iter().flatten() {
+            let work_log = line.work_log().ok().flatten();
+            let tally = line.harvest_tally().ok().flatten();
+
+            let amount = match (work_log, tally) {
+                (Some(log), _) => log.worked_minutes().map(|minutes| {
+                    BillableLine::hours(log.id, String::new(), minutes, line.unit_price).amount
+                }),
+                (_, Some(tally)) => Some(
+                    BillableLine::pieces(tally.id, String::new(), tally.quantity, line.unit_price)
+                        .amount,
+                ),
+                _ => None,
+            };
+
+            if amount != Some(line.amount) {
+                changed_line_ids.push(line.id);
+            }
+            amounts.push(amount.unwrap_or(0.0));
+        }
+
+        let stored = billing::InvoiceTotals {
+            subtotal: data.subtotal,
+            tax: data.tax,
+            total: data.total,
+        };
+        let recomputed = billing::totals(amounts, data.tax_rate);
+
+        Ok(Json::from(ReconciliationBody {
+            reconciliation: Reconciliation {
+                invoice_id: data.id,
+                matches: stored == recomputed && changed_line_ids.is_empty(),
+                stored: stored.into(),
+                recomputed: recomputed.into(),
+                changed_line_ids,
+            },
+        }))
+    }
+
+    /// Prices the employer's unbilled records in the period, leaving out
+    /// rejected logs. Logs awaiting review and records that cannot be priced
+    /// are reported together instead of being left off the invoice.
+    async fn price(
+        tenant: &TenantClient,
+        employer: &employer::Data,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+    ) -> Result<Vec<BillableLine>, AppError> {
+        let work_logs = tenant
+            .work_logs()
+            .find_many(vec![
+                work_log::employer_id::equals(employer.id),
+                work_log::start_time::gte(period_start),
+                work_log::start_time::lt(period_end),
+                work_log::invoice_lines::none(vec![invoice_line::invoice::is(vec![
+                    invoice::status::in_vec(BILLING_STATUSES.to_vec()),
+                ])]),
+            ])
+            .with(work_log::worker::fetch())
+            .order_by(work_log::start_time::order(Direction::Asc))
+            .exec()
+            .await?;
+
+        let tallies = tenant
+            .harvest_tallies()
+            .find_many(vec![
+                harvest_tally::employer_id::equals(employer.id),
+                harvest_tally::recorded_at::gte(period_start),
+                harvest_tally::recorded_at::lt(period_end),
+                harvest_tally::invoice_lines::none(vec![invoice_line::invoice::is(vec![
+                    invoice::status::in_vec(BILLING_STATUSES.to_vec()),
+                ])]),
+            ])
+            .with(harvest_tally::worker::fetch())
+            .order_by(harvest_tally::recorded_at::order(Direction::Asc))
+            .exec()
+            .await?;
+
+        let billing_rates: HashMap<(String, HarvestUnit), f64> = tenant
+            .piece_rates()
+            .find_many(vec![piece_rate::employer_id::equals(employer.id)])
+            .exec()
+            .await?
+            .into_iter()
+            .filter_map(|rate| Some(((rate.crop, rate.unit), rate.billing_rate?)))
+            .collect();
+
+        let mut errors = ValidationErrors::default();
+        let mut lines = vec![];
+
+        for log in &work_logs {
+            let field = format!("workLogs.{}", log.id);
+
+            match log.status {
+                WorkLogStatus::Approved => {}
+                WorkLogStatus::Rejected => continue,
+                WorkLogStatus::Submitted | WorkLogStatus::Amended => {
+                    errors.add(&field, "Awaiting supervisor approval");
+                    continue;
+                }
+            }
+
+            let worked_minutes = match log.worked_minutes() {
+                Some(worked_minutes) => worked_minutes,
+                None => {
+                    errors.add(&field, "Shift has not ended");
+                    continue;
+                }
+            };
+            let rate = match employer.hourly_billing_rate {
+                Some(rate) => rate,
+                None => {
+                    errors.add(&field, "The employer has no hourly billing rate");
+                    continue;
+                }
+            };
+
+            let description = format!(
+                "{} {} ({})",
+                log.start_time.naive_utc().date(),
+                log.job_type,
+                Self::username(log.worker().ok().map(|worker| worker.username.as_str())),
+            );
+            lines.push(BillableLine::hours(
+                log.id,
+                description,
+                worked_minutes,
+                rate,
+            ));
+        }
+
+        for tally in &tallies {
+            match billing_rates.get(&(tally.crop.clone(), tally.unit)) {
+                Some(rate) => {
+                    let description = format!(
+                        "{} {} ({})",
+                        tally.recorded_at.naive_utc().date(),
+                        tally.crop,
+                        Self::username(tally.worker().ok().map(|worker| worker.username.as_str())),
+                    );
+                    lines.push(BillableLine::pieces(
+                        tally.id,
+                        description,
+                        tally.quantity,
+                        *rate,
+                    ));
+                }
+                None => errors.add(
+                    &format!("harvestTallies.{}", tally.id),
+                    "No billing rate for this crop and unit at the employer",
+                ),
+            }
+        }
+
+        errors.into_result()?;
+
+        Ok(lines)
+    }
+
+    /// Moves the invoice to a new status if it is currently in one of `from`.
+    async fn transition(
+        tenant: &TenantClient,
+        id: i32,
+        from: Vec<InvoiceStatus>,
+        data: Vec<invoice::SetParam>,
+        conflict: &str,
+    ) -> Result<Json<InvoiceBody<Invoice>>, AppError> {
+        let updated = tenant
+            .invoices()
+            .update_many(
+                vec![invoice::id::equals(id), invoice::status::in_vec(from)],
+                data,
+            )
+            .exec()
+            .await?;
+
+        if updated == 0 {
+            tenant.invoices().find_by_id(id).await?;
+            return Err(AppError::Conflict(String::from(conflict)));
+        }
+
+        let data = Self::find_invoice(tenant, id).await?;
+
+        Ok(Json::from(InvoiceBody {
+            invoice: data.into(),
+        }))
+    }
+
+    fn billed_elsewhere(invoice_id: i32) -> Vec<invoice_line::WhereParam> {
+        vec![
+            invoice_line::invoice_id::not(invoice_id),
+            invoice_line::invoice::is(vec![invoice::status::in_vec(vec![
+                InvoiceStatus::Issued,
+                InvoiceStatus::Paid,
+            ])]),
+        ]
+    }
+
+    async fn find_invoice(tenant: &TenantClient, id: i32) -> Result<invoice::Data, AppError> {
+        tenant
+            .invoices()
+            .find_first(vec![invoice::id::equals(id)])
+            .with(invoice::lines::fetch(vec![]))
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("Invoice not found")))
+    }
+
+    fn username(username: Option<&str>) -> &str {
+        username.unwrap_or("unknown worker")
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
+}
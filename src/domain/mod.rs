@@ -1,3 +1,14 @@
+pub mod attachments;
+pub mod clock;
+pub mod employers;
+pub mod harvest;
+pub mod imports;
+pub mod invoices;
+pub mod organizations;
+pub mod payroll;
 pub mod profiles;
+pub mod reports;
+pub mod sessions;
 pub mod users;
 pub mod otp;
+pub mod work_logs;
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+pub mod request;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationBody<T> {
+    pub organization: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationsBody<T> {
+    pub organizations: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MembersBody<T> {
+    pub members: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvitationBody<T> {
+    pub invitation: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvitationsBody<T> {
+    pub invitations: Vec<T>,
+}
@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+use crate::prisma::Role;
+
+#[derive(Debug, Deserialize)]
+pub struct OrganizationCreateInput {
+    pub name: String,
+    pub slug: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvitationCreateInput {
+    pub username: String,
+    pub role: Option<Role>,
+}
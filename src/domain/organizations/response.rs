@@ -0,0 +1,98 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{invitation, membership, organization, Role};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub role: Option<Role>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl From<organization::Data> for Organization {
+    fn from(data: organization::Data) -> Self {
+        Self {
+            id: data.id,
+            name: data.name,
+            slug: data.slug,
+            role: None,
+            created_at: data.created_at,
+        }
+    }
+}
+
+impl membership::Data {
+    /// Needs the organization relation fetched.
+    pub fn to_organization(&self) -> Option<Organization> {
+        let organization = self.organization().ok()?;
+
+        Some(Organization {
+            id: organization.id,
+            name: organization.name.clone(),
+            slug: organization.slug.clone(),
+            role: Some(self.role),
+            created_at: organization.created_at,
+        })
+    }
+
+    /// Needs the user relation fetched.
+    pub fn to_member(&self) -> Option<Member> {
+        let user = self.user().ok()?;
+
+        Some(Member {
+            username: user.username.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            role: self.role,
+            joined_at: self.created_at,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Member {
+    pub username: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub role: Role,
+    pub joined_at: DateTime<FixedOffset>,
+}
+
+impl invitation::Data {
+    /// Needs the organization and user relations fetched.
+    pub fn to_invitation(&self) -> Option<Invitation> {
+        let organization = self.organization().ok()?;
+        let user = self.user().ok()?;
+
+        Some(Invitation {
+            id: self.id,
+            organization: Organization {
+                id: organization.id,
+                name: organization.name.clone(),
+                slug: organization.slug.clone(),
+                role: Some(self.role),
+                created_at: organization.created_at,
+            },
+            username: user.username.clone(),
+            role: self.role,
+            invited_at: self.created_at,
+        })
+    }
+}
+
+/// Offer to join `organization`, seen by the invited user and by the
+/// organization's admins.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invitation {
+    pub id: i32,
+    pub organization: Organization,
+    pub username: String,
+    pub role: Role,
+    pub invited_at: DateTime<FixedOffset>,
+}
@@ -0,0 +1,349 @@
+use axum::{http::StatusCode, Extension};
+use lazy_static::lazy_static;
+use prisma_client_rust::{prisma_errors::query_engine::UniqueKeyViolation, Direction, QueryError};
+use regex::Regex;
+use std::sync::Arc;
+
+use crate::{
+    app_error::AppError,
+    domain::sessions::service::SessionsService,
+    extractor::{roles, AuthUser, Json, Path, RequireRole, VerifiedAuthUser},
+    prisma::{invitation, membership, organization, user, PrismaClient, Role},
+    telemetry::{self, Observe},
+    tenant::TenantClient,
+};
+
+use super::{
+    request::{InvitationCreateInput, OrganizationCreateInput},
+    response::{Invitation, Member, Organization},
+    InvitationBody, InvitationsBody, MembersBody, OrganizationBody, OrganizationsBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+lazy_static! {
+    static ref SLUG_REGEX: Regex = Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap();
+}
+
+pub struct OrganizationsService;
+
+impl OrganizationsService {
+    /// Creates an organization with the caller as its first admin. The caller
+    /// switches to it by refreshing their token with `X-Org-Id`. Only
+    /// verified accounts can, which keeps throwaway accounts from squatting
+    /// slugs.
+    pub async fn create_organization(
+        auth_user: VerifiedAuthUser,
+        prisma: Prisma,
+        Json(input): Json<OrganizationBody<OrganizationCreateInput>>,
+    ) -> Result<Json<OrganizationBody<Organization>>, AppError> {
+        let OrganizationBody {
+            organization: OrganizationCreateInput { name, slug },
+        } = input;
+
+        if name.trim().is_empty() {
+            return Err(AppError::BadRequest(String::from(
+                "Organization name must not be empty",
+            )));
+        }
+        if !SLUG_REGEX.is_match(&slug) {
+            return Err(AppError::BadRequest(String::from(
+                "Slug may only contain lowercase letters, digits and dashes",
+            )));
+        }
+
+        let user_id = auth_user.user_id;
+        let data = telemetry::observe_query(
+            "transaction.create_organization",
+            prisma._transaction().run(|tx| async move {
+                let data = tx
+                    .organization()
+                    .create(name, slug, vec![])
+                    .observed()
+                    .exec()
+                    .await?;
+
+                tx.membership()
+                    .create(
+                        organization::id::equals(data.id),
+                        user::id::equals(user_id),
+                        vec![membership::role::set(Role::Admin)],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+
+                Ok::<_, QueryError>(data)
+            }),
+        )
+        .await?;
+
+        let mut organization: Organization = data.into();
+        organization.role = Some(Role::Admin);
+
+        Ok(Json::from(OrganizationBody { organization }))
+    }
+
+    pub async fn list_organizations(
+        auth_user: AuthUser,
+        prisma: Prisma,
+    ) -> Result<Json<OrganizationsBody<Organization>>, AppError> {
+        let organizations = prisma
+            .membership()
+            .find_many(vec![membership::user_id::equals(auth_user.user_id)])
+            .with(membership::organization::fetch())
+            .order_by(membership::id::order(Direction::Asc))
+            .observed()
+            .exec()
+            .await?
+            .iter()
+            .filter_map(membership::Data::to_organization)
+            .collect();
+
+        Ok(Json::from(OrganizationsBody { organizations }))
+    }
+
+    pub async fn list_members(tenant: TenantClient) -> Result<Json<MembersBody<Member>>, AppError> {
+        // Deleted users keep their memberships in case they are restored
+        let active = membership::user::is(vec![user::deleted_at::equals(None)]);
+        let members = tenant
+            .memberships()
+            .find_many(vec![active])
+            .with(membership::user::fetch())
+            .order_by(membership::id::order(Direction::Asc))
+            .exec()
+            .await?
+            .iter()
+            .filter_map(membership::Data::to_member)
+            .collect();
+
+        Ok(Json::from(MembersBody { members }))
+    }
+
+    /// Offers a user membership in the organization. Nothing about the user
+    /// becomes visible to the organization until they accept.
+    pub async fn invite_member(
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<InvitationBody<InvitationCreateInput>>,
+    ) -> Result<Json<InvitationBody<Invitation>>, AppError> {
+        let InvitationBody {
+            invitation: InvitationCreateInput { username, role },
+        } = input;
+
+        let user = prisma
+            .user()
+            .find_unique(user::username::equals(username))
+            .observed()
+            .exec()
+            .await?
+            .filter(|user| user.deleted_at.is_none())
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        let is_member = tenant
+            .memberships()
+            .count(vec![membership::user_id::equals(user.id)])
+            .exec()
+            .await?
+            > 0;
+        if is_member {
+            return Err(AppError::Conflict(String::from(
+                "User is already a member of the organization",
+            )));
+        }
+
+        let data = prisma
+            .invitation()
+            .create(
+                tenant.organization(),
+                user::id::equals(user.id),
+                user::id::equals(admin.user_id),
+                vec![invitation::role::set(role.unwrap_or(Role::Worker))],
+            )
+            .observed()
+            .exec()
+            .await
+            .map_err(|e| {
+                if e.is_prisma_error::<UniqueKeyViolation>() {
+                    AppError::Conflict(String::from("User has already been invited"))
+                } else {
+                    e.into()
+                }
+            })?;
+
+        let invitation = tenant
+            .invitations()
+            .find_first(vec![invitation::id::equals(data.id)])
+            .with(invitation::organization::fetch())
+            .with(invitation::user::fetch())
+            .exec()
+            .await?
+            .as_ref()
+            .and_then(invitation::Data::to_invitation)
+            .ok_or(AppError::NotFound(String::from("Invitation not found")))?;
+
+        Ok(Json::from(InvitationBody { invitation }))
+    }
+
+    /// Invitations of the organization that are still waiting for an answer.
+    pub async fn list_invitations(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<InvitationsBody<Invitation>>, AppError> {
+        let invitations = tenant
+            .invitations()
+            .find_many(vec![])
+            .with(invitation::organization::fetch())
+            .with(invitation::user::fetch())
+            .order_by(invitation::id::order(Direction::Asc))
+            .exec()
+            .await?
+            .iter()
+            .filter_map(invitation::Data::to_invitation)
+            .collect();
+
+        Ok(Json::from(InvitationsBody { invitations }))
+    }
+
+    pub async fn revoke_invitation(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<StatusCode, AppError> {
+        tenant.invitations().delete_by_id(id).await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Invitations addressed to the caller, from any organization.
+    pub async fn list_received_invitations(
+        auth_user: AuthUser,
+        prisma: Prisma,
+    ) -> Result<Json<InvitationsBody<Invitation>>, AppError> {
+        let invitations = prisma
+            .invitation()
+            .find_many(vec![invitation::user_id::equals(auth_user.user_id)])
+            .with(invitation::organization::fetch())
+            .with(invitation::user::fetch())
+            .order_by(invitation::id::order(Direction::Asc))
+            .observed()
+            .exec()
+            .await?
+            .iter()
+            .filter_map(invitation::Data::to_invitation)
+            .collect();
+
+        Ok(Json::from(InvitationsBody { invitations }))
+    }
+
+    /// Turns an invitation addressed to the caller into a membership. The
+    /// caller switches to the organization by refreshing their token with
+    /// `X-Org-Id`.
+    pub async fn accept_invitation(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        prisma: Prisma,
+    ) -> Result<Json<OrganizationBody<Organization>>, AppError> {
+        let data = Self::find_received_invitation(&prisma, &auth_user, id).await?;
+
+        let (organization_id, user_id, role) = (data.organization_id, data.user_id, data.role);
+        telemetry::observe_query(
+            "transaction.accept_invitation",
+            prisma._transaction().run(|tx| async move {
+                tx.invitation()
+                    .delete(invitation::id::equals(id))
+                    .observed()
+                    .exec()
+                    .await?;
+
+                tx.membership()
+                    .create(
+                        organization::id::equals(organization_id),
+                        user::id::equals(user_id),
+                        vec![membership::role::set(role)],
+                    )
+                    .observed()
+                    .exec()
+                    .await
+            }),
+        )
+        .await?;
+
+        let mut organization: Organization = prisma
+            .organization()
+            .find_unique(organization::id::equals(organization_id))
+            .observed()
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("Organization not found")))?
+            .into();
+        organization.role = Some(role);
+
+        Ok(Json::from(OrganizationBody { organization }))
+    }
+
+    pub async fn decline_invitation(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        prisma: Prisma,
+    ) -> Result<StatusCode, AppError> {
+        Self::find_received_invitation(&prisma, &auth_user, id).await?;
+
+        prisma
+            .invitation()
+            .delete(invitation::id::equals(id))
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    pub async fn remove_member(
+        Path(username): Path<String>,
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<StatusCode, AppError> {
+        let user = tenant
+            .users()
+            .find_first(vec![user::username::equals(username)])
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        if user.id == admin.user_id {
+            return Err(AppError::BadRequest(String::from(
+                "You cannot remove yourself from the organization",
+            )));
+        }
+
+        tenant
+            .memberships()
+            .delete_many(vec![membership::user_id::equals(user.id)])
+            .exec()
+            .await?;
+
+        SessionsService::revoke_for_organization(&prisma, user.id, tenant.org_id()).await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn find_received_invitation(
+        prisma: &PrismaClient,
+        auth_user: &AuthUser,
+        id: i32,
+    ) -> Result<invitation::Data, AppError> {
+        prisma
+            .invitation()
+            .find_first(vec![
+                invitation::id::equals(id),
+                invitation::user_id::equals(auth_user.user_id),
+            ])
+            .observed()
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("Invitation not found")))
+    }
+}
@@ -1,19 +1,19 @@
-use axum::{ extract::{ Path, State }, Extension };
+use axum::{ extract::State, Extension };
 use otp_rs::TOTP;
-use prisma_client_rust::chrono::{ DateTime, FixedOffset, TimeZone };
-use regex::Regex;
-use tracing_subscriber::reload::Error;
-use std::{ sync::Arc, time::{ SystemTime, UNIX_EPOCH }, u32 };
-use lettre::message::{ header as other_header, MultiPart, SinglePart };
-use lettre::transport::smtp::authentication::Credentials;
-use lettre::{ Message, SmtpTransport, Transport };
+use prisma_client_rust::chrono::{ self, DateTime, Duration, FixedOffset };
+use prisma_client_rust::Direction;
+use rand::{ rngs::OsRng, RngCore };
+use std::{ sync::Arc, u32 };
 use html_to_string_macro::html;
 use super::{ request::OTPRequestInput, response::OTPResponse, OTPBody };
 use crate::{
     app_error::AppError,
     config::AppContext,
-    extractor::AuthUser,
-    prisma::{ self, user::{ self, email }, PrismaClient },
+    extractor::{ AuthUser, Path },
+    mailer,
+    prisma::{ self, otp_code, user::{ self, email }, OtpPurpose, PrismaClient },
+    telemetry::Observe,
+    validation::{ check_field, rules },
 };
 
 type Prisma = Extension<Arc<PrismaClient>>;
@@ -28,89 +28,203 @@ impl OTPService {
         prisma: Prisma
     ) -> Result<String, AppError> {
         let email_input = &email;
-        Self::is_valid_email(email_input)?;
-        
-        let code = Self::generate_otp(email_input, &ctx).await?;
-        Self::send_email_otp(email_input, &code, &ctx)?;
+        check_field("email", rules::email(email_input))?;
+
+        let taken = prisma
+            .user()
+            .find_first(
+                vec![
+                    user::email::equals(Some(email_input.to_string())),
+                    user::id::not(auth_user.user_id)
+                ]
+            )
+            .observed()
+            .exec().await?;
+        if taken.is_some() {
+            return Err(AppError::Conflict(String::from("Email is already in use")));
+        }
 
-        prisma.user().update(user::id::equals(auth_user.user_id), vec![]).exec().await?;
+        let code = Self::issue_code(
+            &prisma,
+            &ctx,
+            auth_user.user_id,
+            email_input,
+            OtpPurpose::EmailVerification
+        ).await?;
+        Self::send_email_otp(email_input, &code, &ctx)?;
 
-        let response = format!("OTP has been sent to email: {}. code: {}", email, code);
+        let response = format!("OTP has been sent to email: {}", email);
         Ok(response)
     }
 
     pub async fn verify_otp(
         Path(code): Path<u32>,
         auth_user: AuthUser,
-        ctx: State<AppContext>
+        ctx: State<AppContext>,
+        prisma: Prisma
     ) -> Result<String, AppError> {
-        let otp = TOTP::new(&ctx.config.otp.secret);
-        /// Generate code with period and current timestamp
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        let verified = otp.verify(code, ctx.config.otp.exp_in_sec.try_into().unwrap(), timestamp);
-        if verified {
-            Ok("Verified successful!".to_string())
-        } else {
-            Err(AppError::BadRequest(String::from("OTP has expired!")))
+        let otp_code = Self::consume_code(
+            &prisma,
+            &ctx,
+            auth_user.user_id,
+            OtpPurpose::EmailVerification,
+            code
+        ).await?;
+
+        prisma
+            .user()
+            .update(
+                user::id::equals(auth_user.user_id),
+                vec![user::email::set(Some(otp_code.email)), user::verified::set(true)]
+            )
+            .observed()
+            .exec().await?;
+
+        Ok("Verified successful!".to_string())
+    }
+
+    /// Issues a new code for `email`, bound to a fresh per-request secret.
+    /// Any code still pending for the user and `purpose` is invalidated.
+    pub async fn issue_code(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        user_id: i32,
+        email: &str,
+        purpose: OtpPurpose
+    ) -> Result<u32, AppError> {
+        let now = Self::now();
+
+        prisma
+            .otp_code()
+            .update_many(
+                vec![
+                    otp_code::user_id::equals(user_id),
+                    otp_code::purpose::equals(purpose),
+                    otp_code::consumed_at::equals(None),
+                    otp_code::expires_at::gt(now)
+                ],
+                vec![otp_code::expires_at::set(now)]
+            )
+            .observed()
+            .exec().await?;
+
+        let secret = Self::generate_secret();
+        let code = Self::generate_otp(&secret, now.timestamp() as u64, ctx)?;
+
+        prisma
+            .otp_code()
+            .create(
+                user::id::equals(user_id),
+                email.to_string(),
+                secret,
+                now,
+                now + Duration::seconds(ctx.config.otp.exp.as_secs() as i64),
+                vec![otp_code::purpose::set(purpose)]
+            )
+            .observed()
+            .exec().await?;
+
+        Ok(code)
+    }
+
+    /// Checks `code` against the user's pending code and marks it consumed.
+    /// Each attempt counts towards `OTPConfig::max_attempts`, after which the
+    /// code can no longer be used.
+    pub async fn consume_code(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        user_id: i32,
+        purpose: OtpPurpose,
+        code: u32
+    ) -> Result<otp_code::Data, AppError> {
+        let pending = prisma
+            .otp_code()
+            .find_first(
+                vec![
+                    otp_code::user_id::equals(user_id),
+                    otp_code::purpose::equals(purpose),
+                    otp_code::consumed_at::equals(None),
+                    otp_code::expires_at::gt(Self::now())
+                ]
+            )
+            .order_by(otp_code::id::order(Direction::Desc))
+            .observed()
+            .exec().await?
+            .ok_or(AppError::BadRequest(String::from("OTP has expired!")))?;
+
+        let counted = prisma
+            .otp_code()
+            .update_many(
+                vec![
+                    otp_code::id::equals(pending.id),
+                    otp_code::attempts::lt(ctx.config.otp.max_attempts)
+                ],
+                vec![otp_code::attempts::increment(1)]
+            )
+            .observed()
+            .exec().await?;
+
+        if counted == 0 {
+            return Err(AppError::TooManyRequests(String::from("Too many OTP attempts")));
+        }
+
+        let expected = Self::generate_otp(
+            &pending.secret,
+            pending.issued_at.timestamp() as u64,
+            ctx
+        )?;
+        if expected != code {
+            return Err(AppError::BadRequest(String::from("Invalid OTP")));
+        }
+
+        let consumed = prisma
+            .otp_code()
+            .update_many(
+                vec![otp_code::id::equals(pending.id), otp_code::consumed_at::equals(None)],
+                vec![otp_code::consumed_at::set(Some(Self::now()))]
+            )
+            .observed()
+            .exec().await?;
+
+        if consumed == 0 {
+            return Err(AppError::BadRequest(String::from("OTP has already been used")));
         }
+
+        Ok(pending)
     }
 
-    async fn generate_otp(email: &str, ctx: &State<AppContext>) -> anyhow::Result<u32> {
-        let otp = TOTP::new(&ctx.config.otp.secret);
-        /// Generate code with period and current timestamp
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    fn generate_otp(secret: &str, timestamp: u64, ctx: &AppContext) -> Result<u32, AppError> {
+        // The stored per-request secret is peppered with the server secret so
+        // a leaked database row alone is not enough to derive the code.
+        let otp = TOTP::new(&format!("{}{}", ctx.config.otp.secret, secret));
         let code = otp
-            .generate(ctx.config.otp.exp_in_sec.try_into().unwrap(), timestamp)
+            .generate(ctx.config.otp.exp.as_secs(), timestamp)
             .map_err(|_| anyhow::anyhow!("Fail to create OTP code!"))?;
 
         Ok(code)
     }
 
-    fn is_valid_email(email: &str) -> Result<(), AppError> {
-        let email_regex = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
-        if !email_regex.is_match(email) {
-            return Err(AppError::BadRequest(String::from("Invalid email format")));
-        }
-        Ok(())
+    fn generate_secret() -> String {
+        let mut bytes = [0u8; 20];
+        OsRng.fill_bytes(&mut bytes);
+        hex::encode(bytes)
     }
 
-    fn send_email_otp(
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
+
+    pub fn send_email_otp(
         to_email: &String,
         code: &u32,
-        ctx: &State<AppContext>
+        ctx: &AppContext
     ) -> Result<(), AppError> {
-        let email = Message::builder()
-            .from(
-                format!("{} <ducle6487@gmail.com>", &ctx.config.smtp.name).parse().unwrap()
-            )
-            .to(format!("<{}>", &to_email).parse().unwrap())
-            .subject(String::from("Login Code: ".to_string() + &code.to_string()))
-            .multipart(
-                MultiPart::alternative().singlepart(
-                    SinglePart::builder()
-                        .header(lettre::message::header::ContentType::TEXT_HTML)
-                        .body(String::from(Self::get_email_body(&code)))
-                )
-            )
-            .unwrap();
-
-        let creds = Credentials::new(
-            ctx.config.smtp.email.to_owned(),
-            ctx.config.smtp.password.to_owned()
-        );
-
-        // Open a remote connection to gmail
-        let mailer = SmtpTransport::starttls_relay(&ctx.config.smtp.server)
-            .unwrap()
-            .port(ctx.config.smtp.port)
-            .credentials(creds)
-            .build();
-
-        // Send the email
-        match mailer.send(&email) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(AppError::BadRequest(String::from(e.to_string()))),
-        }
+        mailer::send_html(
+            ctx,
+            to_email,
+            &format!("Login Code: {}", code),
+            Self::get_email_body(&code)
+        )
     }
 
     fn get_email_body(code: &u32) -> String {
@@ -0,0 +1,114 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+
+/// Pay rules applied to a whole run.
+#[derive(Debug, Clone)]
+pub struct PayPolicy {
+    /// Minutes per worker per day paid at the regular rate.
+    pub overtime_threshold_minutes: i64,
+    pub overtime_multiplier: f64,
+    /// Share of gross pay withheld, between 0 and 1.
+    pub deduction_rate: f64,
+}
+
+/// A closed work log with the hourly rate it is paid at.
+#[derive(Debug, Clone)]
+pub struct Shift {
+    pub worker_id: i32,
+    pub date: NaiveDate,
+    pub start: DateTime<FixedOffset>,
+    pub worked_minutes: i64,
+    pub hourly_rate: f64,
+}
+
+/// A harvest tally with the piece rate it is paid at.
+#[derive(Debug, Clone)]
+pub struct PieceWork {
+    pub worker_id: i32,
+    pub quantity: f64,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayLine {
+    pub worker_id: i32,
+    pub regular_minutes: i64,
+    pub overtime_minutes: i64,
+    pub hourly_pay: f64,
+    pub overtime_pay: f64,
+    pub piece_pay: f64,
+    pub gross_pay: f64,
+    pub deductions: f64,
+    pub net_pay: f64,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    regular_minutes: i64,
+    overtime_minutes: i64,
+    hourly_pay: f64,
+    overtime_pay: f64,
+    piece_pay: f64,
+}
+
+/// Computes one pay line per worker, ordered by worker id.
+///
+/// Shifts are taken in chronological order per day, so the minutes past the
+/// daily threshold are paid at the rate of the shift they fall into. Amounts
+/// are rounded to cents per component before summing, which keeps stored
+/// totals reproducible from the line items.
+pub fn calculate(policy: &PayPolicy, shifts: &[Shift], pieces: &[PieceWork]) -> Vec<PayLine> {
+    let mut shifts: Vec<&Shift> = shifts.iter().collect();
+    shifts.sort_by_key(|shift| (shift.worker_id, shift.start));
+
+    let mut workers: BTreeMap<i32, Accumulator> = BTreeMap::new();
+    let mut minutes_per_day: HashMap<(i32, NaiveDate), i64> = HashMap::new();
+
+    for shift in shifts {
+        let worked_today = minutes_per_day
+            .entry((shift.worker_id, shift.date))
+            .or_insert(0);
+        let regular_left = (policy.overtime_threshold_minutes - *worked_today).max(0);
+        let regular = shift.worked_minutes.min(regular_left);
+        let overtime = shift.worked_minutes - regular;
+        *worked_today += shift.worked_minutes;
+
+        let worker = workers.entry(shift.worker_id).or_default();
+        worker.regular_minutes += regular;
+        worker.overtime_minutes += overtime;
+        worker.hourly_pay += regular as f64 / 60.0 * shift.hourly_rate;
+        worker.overtime_pay +=
+            overtime as f64 / 60.0 * shift.hourly_rate * policy.overtime_multiplier;
+    }
+
+    for piece in pieces {
+        workers.entry(piece.worker_id).or_default().piece_pay += piece.quantity * piece.rate;
+    }
+
+    workers
+        .into_iter()
+        .map(|(worker_id, worker)| {
+            let hourly_pay = round_cents(worker.hourly_pay);
+            let overtime_pay = round_cents(worker.overtime_pay);
+            let piece_pay = round_cents(worker.piece_pay);
+            let gross_pay = round_cents(hourly_pay + overtime_pay + piece_pay);
+            let deductions = round_cents(gross_pay * policy.deduction_rate);
+
+            PayLine {
+                worker_id,
+                regular_minutes: worker.regular_minutes,
+                overtime_minutes: worker.overtime_minutes,
+                hourly_pay,
+                overtime_pay,
+                piece_pay,
+                gross_pay,
+                deductions,
+                net_pay: round_cents(gross_pay - deductions),
+            }
+        })
+        .collect()
+}
+
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
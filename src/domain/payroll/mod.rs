@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+pub mod calculator;
+pub mod request;
+pub mod response;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollRunBody<T> {
+    pub payroll_run: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollRunsBody<T> {
+    pub payroll_runs: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceRateBody<T> {
+    pub piece_rate: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceRatesBody<T> {
+    pub piece_rates: Vec<T>,
+}
@@ -0,0 +1,29 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use crate::prisma::HarvestUnit;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollRunCreateInput {
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+    /// Defaults to 480 (8 hours a day).
+    pub overtime_threshold_minutes: Option<i32>,
+    /// Defaults to 1.5.
+    pub overtime_multiplier: Option<f64>,
+    /// Defaults to 0.
+    pub deduction_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceRateInput {
+    pub employer_id: i32,
+    pub crop: String,
+    pub unit: HarvestUnit,
+    /// Paid to the worker per unit.
+    pub rate: f64,
+    /// Charged to the employer per unit on invoices.
+    pub billing_rate: Option<f64>,
+}
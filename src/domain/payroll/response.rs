@@ -0,0 +1,156 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{payroll_line_item, payroll_run, piece_rate, HarvestUnit, PayrollRunStatus};
+
+use super::calculator::{round_cents, PayLine};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollLineItem {
+    pub worker_id: i32,
+    pub username: Option<String>,
+    pub regular_minutes: i64,
+    pub overtime_minutes: i64,
+    pub hourly_pay: f64,
+    pub overtime_pay: f64,
+    pub piece_pay: f64,
+    pub gross_pay: f64,
+    pub deductions: f64,
+    pub net_pay: f64,
+}
+
+/// A run computed but not stored.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollPreview {
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+    pub overtime_threshold_minutes: i32,
+    pub overtime_multiplier: f64,
+    pub deduction_rate: f64,
+    pub total_gross: f64,
+    pub total_deductions: f64,
+    pub total_net: f64,
+    pub line_items: Vec<PayrollLineItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollRun {
+    pub id: i32,
+    pub status: PayrollRunStatus,
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+    pub overtime_threshold_minutes: i32,
+    pub overtime_multiplier: f64,
+    pub deduction_rate: f64,
+    pub total_gross: f64,
+    pub total_deductions: f64,
+    pub total_net: f64,
+    pub created_by_id: i32,
+    pub finalized_by_id: Option<i32>,
+    pub finalized_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_items: Option<Vec<PayrollLineItem>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceRate {
+    pub id: i32,
+    pub employer_id: i32,
+    pub crop: String,
+    pub unit: HarvestUnit,
+    pub rate: f64,
+    pub billing_rate: Option<f64>,
+}
+
+/// Sums gross, deductions and net pay over the lines.
+pub fn totals(lines: &[PayLine]) -> (f64, f64, f64) {
+    let (gross, deductions, net) = lines.iter().fold((0.0, 0.0, 0.0), |acc, line| {
+        (
+            acc.0 + line.gross_pay,
+            acc.1 + line.deductions,
+            acc.2 + line.net_pay,
+        )
+    });
+
+    (
+        round_cents(gross),
+        round_cents(deductions),
+        round_cents(net),
+    )
+}
+
+impl From<PayLine> for PayrollLineItem {
+    fn from(line: PayLine) -> Self {
+        Self {
+            worker_id: line.worker_id,
+            username: None,
+            regular_minutes: line.regular_minutes,
+            overtime_minutes: line.overtime_minutes,
+            hourly_pay: line.hourly_pay,
+            overtime_pay: line.overtime_pay,
+            piece_pay: line.piece_pay,
+            gross_pay: line.gross_pay,
+            deductions: line.deductions,
+            net_pay: line.net_pay,
+        }
+    }
+}
+
+impl From<payroll_line_item::Data> for PayrollLineItem {
+    fn from(data: payroll_line_item::Data) -> Self {
+        Self {
+            username: data.worker().ok().map(|worker| worker.username.clone()),
+            worker_id: data.worker_id,
+            regular_minutes: data.regular_minutes as i64,
+            overtime_minutes: data.overtime_minutes as i64,
+            hourly_pay: data.hourly_pay,
+            overtime_pay: data.overtime_pay,
+            piece_pay: data.piece_pay,
+            gross_pay: data.gross_pay,
+            deductions: data.deductions,
+            net_pay: data.net_pay,
+        }
+    }
+}
+
+impl From<payroll_run::Data> for PayrollRun {
+    fn from(data: payroll_run::Data) -> Self {
+        Self {
+            line_items: data
+                .line_items
+                .map(|items| items.into_iter().map(PayrollLineItem::from).collect()),
+            id: data.id,
+            status: data.status,
+            period_start: data.period_start,
+            period_end: data.period_end,
+            overtime_threshold_minutes: data.overtime_threshold_minutes,
+            overtime_multiplier: data.overtime_multiplier,
+            deduction_rate: data.deduction_rate,
+            total_gross: data.total_gross,
+            total_deductions: data.total_deductions,
+            total_net: data.total_net,
+            created_by_id: data.created_by_id,
+            finalized_by_id: data.finalized_by_id,
+            finalized_at: data.finalized_at,
+            created_at: data.created_at,
+        }
+    }
+}
+
+impl From<piece_rate::Data> for PieceRate {
+    fn from(data: piece_rate::Data) -> Self {
+        Self {
+            id: data.id,
+            employer_id: data.employer_id,
+            crop: data.crop,
+            unit: data.unit,
+            rate: data.rate,
+            billing_rate: data.billing_rate,
+        }
+    }
+}
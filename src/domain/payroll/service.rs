@@ -0,0 +1,550 @@
+use axum::Extension;
+use prisma_client_rust::{
+    chrono::{self, DateTime, Duration, FixedOffset},
+    operator::or,
+    Direction,
+};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    app_error::AppError,
+    extractor::{roles, Json, Path, RequireRole},
+    prisma::{
+        assignment, employer, harvest_tally, organization, payroll_line_item, payroll_run,
+        piece_rate, user, work_log, HarvestUnit, PayrollRunStatus, PrismaClient, WorkLogStatus,
+    },
+    telemetry::{self, Observe},
+    tenant::TenantClient,
+    validation::ValidationErrors,
+};
+
+use super::{
+    calculator::{self, PayLine, PayPolicy, PieceWork, Shift},
+    request::{PayrollRunCreateInput, PieceRateInput},
+    response::{totals, PayrollLineItem, PayrollPreview, PayrollRun, PieceRate},
+    PayrollRunBody, PayrollRunsBody, PieceRateBody, PieceRatesBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+const DEFAULT_OVERTIME_THRESHOLD_MINUTES: i32 = 480;
+const DEFAULT_OVERTIME_MULTIPLIER: f64 = 1.5;
+const MAX_PERIOD_DAYS: i64 = 62;
+
+/// Result of pricing every unpaid record in a period.
+struct Computation {
+    lines: Vec<PayLine>,
+    work_log_ids: Vec<i32>,
+    tally_ids: Vec<i32>,
+}
+
+pub struct PayrollService;
+
+impl PayrollService {
+    pub async fn preview_run(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        Json(input): Json<PayrollRunBody<PayrollRunCreateInput>>,
+    ) -> Result<Json<PayrollRunBody<PayrollPreview>>, AppError> {
+        let PayrollRunBody { payroll_run: input } = input;
+        let policy = Self::policy(&input)?;
+
+        let computation =
+            Self::compute(&tenant, input.period_start, input.period_end, &policy).await?;
+        let (total_gross, total_deductions, total_net) = totals(&computation.lines);
+
+        Ok(Json::from(PayrollRunBody {
+            payroll_run: PayrollPreview {
+                period_start: input.period_start,
+                period_end: input.period_end,
+                overtime_threshold_minutes: policy.overtime_threshold_minutes as i32,
+                overtime_multiplier: policy.overtime_multiplier,
+                deduction_rate: policy.deduction_rate,
+                total_gross,
+                total_deductions,
+                total_net,
+                line_items: computation
+                    .lines
+                    .into_iter()
+                    .map(PayrollLineItem::from)
+                    .collect(),
+            },
+        }))
+    }
+
+    /// Stores a draft run. Drafts do not lock anything; the figures are
+    /// recomputed when the run is finalized.
+    pub async fn create_run(
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<PayrollRunBody<PayrollRunCreateInput>>,
+    ) -> Result<Json<PayrollRunBody<PayrollRun>>, AppError> {
+        let PayrollRunBody { payroll_run: input } = input;
+        let policy = Self::policy(&input)?;
+
+        let computation =
+            Self::compute(&tenant, input.period_start, input.period_end, &policy).await?;
+        let (total_gross, total_deductions, total_net) = totals(&computation.lines);
+
+        let org_id = tenant.org_id();
+        let admin_id = admin.user_id;
+        let lines = computation.lines;
+
+        let run_id = telemetry::observe_query(
+            "transaction.create_run",
+            prisma._transaction().run(|tx| async move {
+                let run = tx
+                    .payroll_run()
+                    .create(
+                        organization::id::equals(org_id),
+                        input.period_start,
+                        input.period_end,
+                        policy.overtime_threshold_minutes as i32,
+                        policy.overtime_multiplier,
+                        policy.deduction_rate,
+                        total_gross,
+                        total_deductions,
+                        total_net,
+                        user::id::equals(admin_id),
+                        vec![],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+
+                Self::create_line_items(&tx, run.id, &lines).await?;
+
+                Ok::<_, AppError>(run.id)
+            }),
+        )
+        .await?;
+
+        let data = Self::find_run(&tenant, run_id).await?;
+
+        Ok(Json::from(PayrollRunBody {
+            payroll_run: data.into(),
+        }))
+    }
+
+    pub async fn list_runs(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<PayrollRunsBody<PayrollRun>>, AppError> {
+        let payroll_runs = tenant
+            .payroll_runs()
+            .find_many(vec![])
+            .order_by(payroll_run::period_start::order(Direction::Desc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(PayrollRun::from)
+            .collect();
+
+        Ok(Json::from(PayrollRunsBody { payroll_runs }))
+    }
+
+    pub async fn get_run(
+        Path(id): Path<i32>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+    ) -> Result<Json<PayrollRunBody<PayrollRun>>, AppError> {
+        let data = Self::find_run(&tenant, id).await?;
+
+        Ok(Json::from(PayrollRunBody {
+            payroll_run: data.into(),
+        }))
+    }
+
+    /// Recomputes a draft from the current records, stores the final line
+    /// items and attaches every paid work log and tally to the run. Attached
+    /// records can no longer be edited and are left out of later runs.
+    pub async fn finalize_run(
+        Path(id): Path<i32>,
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<PayrollRunBody<PayrollRun>>, AppError> {
+        let run = tenant.payroll_runs().find_by_id(id).await?;
+        if run.status != PayrollRunStatus::Draft {
+            return Err(Self::already_finalized());
+        }
+
+        let policy = PayPolicy {
+            overtime_threshold_minutes: run.overtime_threshold_minutes as i64,
+            overtime_multiplier: run.overtime_multiplier,
+            deduction_rate: run.deduction_rate,
+        };
+        let Computation {
+            lines,
+            work_log_ids,
+            tally_ids,
+        } = Self::compute(&tenant, run.period_start, run.period_end, &policy).await?;
+        let (total_gross, total_deductions, total_net) = totals(&lines);
+
+        let admin_id = admin.user_id;
+        let now: DateTime<FixedOffset> = chrono::Utc::now().into();
+
+        telemetry::observe_query(
+            "transaction.finalize_run",
+            prisma._transaction().run(|tx| async move {
+                // Claiming the draft first makes a concurrent finalize of the
+                // same run a no-op
+                let claimed = tx
+                    .payroll_run()
+                    .update_many(
+                        vec![
+                            payroll_run::id::equals(run.id),
+                            payroll_run::status::equals(PayrollRunStatus::Draft),
+                        ],
+                        vec![
+                            payroll_run::status::set(PayrollRunStatus::Finalized),
+                            payroll_run::finalized_by_id::set(Some(admin_id)),
+                            payroll_run::finalized_at::set(Some(now)),
+                            payroll_run::total_gross::set(total_gross),
+                            payroll_run::total_deductions::set(total_deductions),
+                            payroll_run::total_net::set(total_net),
+                        ],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+                if claimed == 0 {
+                    return Err(Self::already_finalized());
+                }
+
+                tx.payroll_line_item()
+                    .delete_many(vec![payroll_line_item::payroll_run_id::equals(run.id)])
+                    .observed()
+                    .exec()
+                    .await?;
+                Self::create_line_items(&tx, run.id, &lines).await?;
+
+                // Records paid by another run in the meantime make these
+                // figures wrong, so roll everything back
+                let locked_logs = tx
+                    .work_log()
+                    .update_many(
+                        vec![
+                            work_log::id::in_vec(work_log_ids.clone()),
+                            work_log::payroll_run_id::equals(None),
+                        ],
+                        vec![work_log::payroll_run_id::set(Some(run.id))],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+                let locked_tallies = tx
+                    .harvest_tally()
+                    .update_many(
+                        vec![
+                            harvest_tally::id::in_vec(tally_ids.clone()),
+                            harvest_tally::payroll_run_id::equals(None),
+                        ],
+                        vec![harvest_tally::payroll_run_id::set(Some(run.id))],
+                    )
+                    .observed()
+                    .exec()
+                    .await?;
+                if locked_logs as usize != work_log_ids.len()
+                    || locked_tallies as usize != tally_ids.len()
+                {
+                    return Err(AppError::Conflict(String::from(
+                        "Some records were paid by another run, please retry",
+                    )));
+                }
+
+                Ok(())
+            }),
+        )
+        .await?;
+
+        let data = Self::find_run(&tenant, id).await?;
+
+        Ok(Json::from(PayrollRunBody {
+            payroll_run: data.into(),
+        }))
+    }
+
+    pub async fn upsert_piece_rate(
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<PieceRateBody<PieceRateInput>>,
+    ) -> Result<Json<PieceRateBody<PieceRate>>, AppError> {
+        let PieceRateBody {
+            piece_rate:
+                PieceRateInput {
+                    employer_id,
+                    crop,
+                    unit,
+                    rate,
+                    billing_rate,
+                },
+        } = input;
+
+        if crop.trim().is_empty() {
+            return Err(AppError::BadRequest(String::from("Crop must not be empty")));
+        }
+        if !rate.is_finite() || rate < 0.0 {
+            return Err(AppError::BadRequest(String::from(
+                "Rate must not be negative",
+            )));
+        }
+        if billing_rate.is_some_and(|rate| !rate.is_finite() || rate < 0.0) {
+            return Err(AppError::BadRequest(String::from(
+                "Billing rate must not be negative",
+            )));
+        }
+
+        let employer = tenant.employers().find_by_id(employer_id).await?;
+
+        let data = prisma
+            .piece_rate()
+            .upsert(
+                piece_rate::employer_id_crop_unit(employer.id, crop.clone(), unit),
+                piece_rate::create(
+                    tenant.organization(),
+                    employer::id::equals(employer.id),
+                    crop,
+                    unit,
+                    rate,
+                    vec![piece_rate::billing_rate::set(billing_rate)],
+                ),
+                vec![
+                    piece_rate::rate::set(rate),
+                    piece_rate::billing_rate::set(billing_rate),
+                ],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(Json::from(PieceRateBody {
+            piece_rate: data.into(),
+        }))
+    }
+
+    pub async fn list_piece_rates(
+        tenant: TenantClient,
+    ) -> Result<Json<PieceRatesBody<PieceRate>>, AppError> {
+        let piece_rates = tenant
+            .piece_rates()
+            .find_many(vec![])
+            .order_by(piece_rate::crop::order(Direction::Asc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(PieceRate::from)
+            .collect();
+
+        Ok(Json::from(PieceRatesBody { piece_rates }))
+    }
+
+    /// Prices every approved work log and every tally in
+    /// `[period_start, period_end)` that no finalized run has paid yet.
+    /// Rejected logs are left out. Logs still awaiting review and records
+    /// that cannot be priced are reported together instead of being skipped,
+    /// so nobody is silently underpaid.
+    async fn compute(
+        tenant: &TenantClient,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+        policy: &PayPolicy,
+    ) -> Result<Computation, AppError> {
+        let work_logs = tenant
+            .work_logs()
+            .find_many(vec![
+                work_log::start_time::gte(period_start),
+                work_log::start_time::lt(period_end),
+                work_log::payroll_run_id::equals(None),
+            ])
+            .exec()
+            .await?;
+
+        let tallies = tenant
+            .harvest_tallies()
+            .find_many(vec![
+                harvest_tally::recorded_at::gte(period_start),
+                harvest_tally::recorded_at::lt(period_end),
+                harvest_tally::payroll_run_id::equals(None),
+            ])
+            .exec()
+            .await?;
+
+        let assignments = tenant
+            .assignments()
+            .find_many(vec![
+                assignment::start_date::lt(period_end),
+                or(vec![
+                    assignment::end_date::equals(None),
+                    assignment::end_date::gte(period_start),
+                ]),
+            ])
+            .exec()
+            .await?;
+
+        let piece_rates: HashMap<(i32, String, HarvestUnit), f64> = tenant
+            .piece_rates()
+            .find_many(vec![])
+            .exec()
+            .await?
+            .into_iter()
+            .map(|rate| ((rate.employer_id, rate.crop, rate.unit), rate.rate))
+            .collect();
+
+        let mut errors = ValidationErrors::default();
+
+        let mut shifts = vec![];
+        let mut work_log_ids = vec![];
+        for log in &work_logs {
+            let field = format!("workLogs.{}", log.id);
+
+            match log.status {
+                WorkLogStatus::Approved => {}
+                WorkLogStatus::Rejected => continue,
+                WorkLogStatus::Submitted | WorkLogStatus::Amended => {
+                    errors.add(&field, "Awaiting supervisor approval");
+                    continue;
+                }
+            }
+
+            let worked_minutes = match log.worked_minutes() {
+                Some(worked_minutes) => worked_minutes,
+                None => {
+                    errors.add(&field, "Shift has not ended");
+                    continue;
+                }
+            };
+
+            // The most recent assignment covering the shift sets its rate
+            let hourly_rate = assignments
+                .iter()
+                .filter(|assignment| {
+                    assignment.user_id == log.worker_id
+                        && assignment.employer_id == log.employer_id
+                        && assignment.start_date <= log.start_time
+                        && assignment
+                            .end_date
+                            .map_or(true, |end_date| end_date >= log.start_time)
+                })
+                .max_by_key(|assignment| assignment.start_date)
+                .map(|assignment| assignment.rate);
+
+            match hourly_rate {
+                Some(hourly_rate) => {
+                    shifts.push(Shift {
+                        worker_id: log.worker_id,
+                        date: log.start_time.naive_utc().date(),
+                        start: log.start_time,
+                        worked_minutes,
+                        hourly_rate,
+                    });
+                    work_log_ids.push(log.id);
+                }
+                None => errors.add(&field, "No assignment covers this shift"),
+            }
+        }
+
+        let mut pieces = vec![];
+        for tally in &tallies {
+            match piece_rates.get(&(tally.employer_id, tally.crop.clone(), tally.unit)) {
+                Some(rate) => pieces.push(PieceWork {
+                    worker_id: tally.worker_id,
+                    quantity: tally.quantity,
+                    rate: *rate,
+                }),
+                None => errors.add(
+                    &format!("harvestTallies.{}", tally.id),
+                    "No piece rate for this crop and unit at the employer",
+                ),
+            }
+        }
+
+        errors.into_result()?;
+
+        Ok(Computation {
+            lines: calculator::calculate(policy, &shifts, &pieces),
+            work_log_ids,
+            tally_ids: tallies.iter().map(|tally| tally.id).collect(),
+        })
+    }
+
+    fn policy(input: &PayrollRunCreateInput) -> Result<PayPolicy, AppError> {
+        let mut errors = ValidationErrors::default();
+
+        if input.period_end <= input.period_start {
+            errors.add("periodEnd", "Must be after the period start");
+        } else if input.period_end - input.period_start > Duration::days(MAX_PERIOD_DAYS) {
+            errors.add("periodEnd", "A pay period may span at most 62 days");
+        }
+
+        let policy = PayPolicy {
+            overtime_threshold_minutes: input
+                .overtime_threshold_minutes
+                .unwrap_or(DEFAULT_OVERTIME_THRESHOLD_MINUTES)
+                as i64,
+            overtime_multiplier: input
+                .overtime_multiplier
+                .unwrap_or(DEFAULT_OVERTIME_MULTIPLIER),
+            deduction_rate: input.deduction_rate.unwrap_or(0.0),
+        };
+
+        if policy.overtime_threshold_minutes < 0 {
+            errors.add("overtimeThresholdMinutes", "Must not be negative");
+        }
+        if !policy.overtime_multiplier.is_finite() || policy.overtime_multiplier < 1.0 {
+            errors.add("overtimeMultiplier", "Must be at least 1");
+        }
+        if !(0.0..=1.0).contains(&policy.deduction_rate) {
+            errors.add("deductionRate", "Must be between 0 and 1");
+        }
+
+        errors.into_result()?;
+
+        Ok(policy)
+    }
+
+    async fn create_line_items(
+        prisma: &PrismaClient,
+        run_id: i32,
+        lines: &[PayLine],
+    ) -> Result<(), AppError> {
+        for line in lines {
+            prisma
+                .payroll_line_item()
+                .create(
+                    payroll_run::id::equals(run_id),
+                    user::id::equals(line.worker_id),
+                    line.regular_minutes as i32,
+                    line.overtime_minutes as i32,
+                    line.hourly_pay,
+                    line.overtime_pay,
+                    line.piece_pay,
+                    line.gross_pay,
+                    line.deductions,
+                    line.net_pay,
+                    vec![],
+                )
+                .observed()
+                .exec()
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn find_run(tenant: &TenantClient, id: i32) -> Result<payroll_run::Data, AppError> {
+        tenant
+            .payroll_runs()
+            .find_first(vec![payroll_run::id::equals(id)])
+            .with(payroll_run::line_items::fetch(vec![]).with(payroll_line_item::worker::fetch()))
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("Payroll run not found")))
+    }
+
+    fn already_finalized() -> AppError {
+        AppError::Conflict(String::from("Payroll run is already finalized"))
+    }
+}
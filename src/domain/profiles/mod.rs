@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+pub mod request;
 pub mod response;
 pub mod service;
 
@@ -7,3 +8,24 @@ pub mod service;
 pub struct ProfileBody<T> {
     pub profile: T,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesBody<T> {
+    pub profiles: Vec<T>,
+    pub profiles_count: i64,
+}
+
+/// One page of the directory. `nextCursor` is absent on the last page.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesPageBody<T> {
+    pub profiles: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkHistoryBody<T> {
+    pub work_history: Vec<T>,
+}
@@ -0,0 +1,47 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use crate::prisma::Role;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkHistoryQuery {
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+}
+
+/// Filters of the user directory. `q` matches the username, first or last
+/// name, `role` is the role in the current organization and `employerId`
+/// the employer of a current assignment.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileListQuery {
+    pub q: Option<String>,
+    pub verified: Option<bool>,
+    pub role: Option<Role>,
+    pub employer_id: Option<i32>,
+    pub sort: Option<ProfileSort>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+/// A leading `-` sorts descending.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum ProfileSort {
+    #[default]
+    #[serde(rename = "username")]
+    Username,
+    #[serde(rename = "-username")]
+    UsernameDesc,
+    #[serde(rename = "createdAt")]
+    CreatedAt,
+    #[serde(rename = "-createdAt")]
+    CreatedAtDesc,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
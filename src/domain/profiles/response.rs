@@ -1,6 +1,7 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-use crate::prisma::user;
+use crate::prisma::{assignment, user, HarvestUnit};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Profile {
@@ -8,6 +9,47 @@ pub struct Profile {
     pub bio: Option<String>,
     pub image: Option<String>,
     pub following: bool,
+    #[serde(rename = "currentAssignment")]
+    pub current_assignment: Option<ProfileAssignment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileAssignment {
+    pub employer_id: i32,
+    pub employer_name: Option<String>,
+    pub start_date: DateTime<FixedOffset>,
+    pub end_date: Option<DateTime<FixedOffset>>,
+}
+
+/// One day of a worker's history: hours from work logs next to piece-rate
+/// output from harvest tallies.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkHistoryDay {
+    pub date: NaiveDate,
+    pub shifts: i64,
+    pub worked_minutes: i64,
+    pub output: Vec<PieceOutput>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceOutput {
+    pub crop: String,
+    pub unit: HarvestUnit,
+    pub quantity: f64,
+}
+
+impl WorkHistoryDay {
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            shifts: 0,
+            worked_minutes: 0,
+            output: vec![],
+        }
+    }
 }
 
 impl user::Data {
@@ -17,10 +59,25 @@ impl user::Data {
             bio: self.bio,
             image: self.image,
             following,
+            current_assignment: None,
         }
     }
 }
 
+impl Profile {
+    pub fn set_current_assignment(&mut self, assignment: assignment::Data) {
+        self.current_assignment = Some(ProfileAssignment {
+            employer_name: assignment
+                .employer()
+                .ok()
+                .map(|employer| employer.name.clone()),
+            employer_id: assignment.employer_id,
+            start_date: assignment.start_date,
+            end_date: assignment.end_date,
+        });
+    }
+}
+
 impl From<user::Data> for Profile {
     fn from(data: user::Data) -> Self {
         Self {
@@ -28,6 +85,7 @@ impl From<user::Data> for Profile {
             bio: data.bio,
             image: data.image,
             following: false,
+            current_assignment: None,
         }
     }
 }
@@ -1,33 +1,474 @@
-use axum::{extract::Path, Extension, Json};
-use std::sync::Arc;
+use axum::Extension;
+use prisma_client_rust::{
+    chrono::{self, DateTime, FixedOffset},
+    operator::{and, or},
+    prisma_errors::query_engine::UniqueKeyViolation,
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
 
 use crate::{
     app_error::AppError,
-    extractor::{AuthUser, OptionalAuthUser},
-    prisma::{user, PrismaClient},
+    domain::{employers::service::EmployersService, harvest::service::HarvestService},
+    extractor::{roles, AuthUser, Json, Path, Query, RoleRequirement},
+    pagination::{clamp_limit, date_range, Cursor, Page, PageRequest},
+    prisma::{assignment, follow, harvest_tally, membership, user, work_log, PrismaClient},
+    telemetry::Observe,
+    tenant::TenantClient,
 };
 
-use super::{response::Profile, ProfileBody};
+use super::{
+    request::{FollowListQuery, ProfileListQuery, ProfileSort, WorkHistoryQuery},
+    response::{PieceOutput, Profile, WorkHistoryDay},
+    ProfileBody, ProfilesBody, ProfilesPageBody, WorkHistoryBody,
+};
 
 type Prisma = Extension<Arc<PrismaClient>>;
 
+const HISTORY_DEFAULT_DAYS: i64 = 30;
+const HISTORY_MAX_DAYS: i64 = 366;
+
+/// Sort key of the last profile of a directory page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ProfileKey {
+    Username(String),
+    CreatedAt(DateTime<FixedOffset>),
+}
+
 pub struct ProfilesService;
 
 impl ProfilesService {
+    /// Searches the members of the organization, one page at a time.
+    pub async fn list_profiles(
+        Query(query): Query<ProfileListQuery>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<ProfilesPageBody<Profile>>, AppError> {
+        let sort = query.sort.unwrap_or_default();
+        let page = PageRequest::<ProfileKey>::new(query.limit, query.cursor.as_deref())?;
+
+        let mut filters = Self::directory_filters(&tenant, &query);
+        if let Some(after) = &page.after {
+            filters.push(Self::after(sort, after)?);
+        }
+
+        let (order, direction) = match sort {
+            ProfileSort::Username => (user::username::order(Direction::Asc), Direction::Asc),
+            ProfileSort::UsernameDesc => (user::username::order(Direction::Desc), Direction::Desc),
+            ProfileSort::CreatedAt => (user::created_at::order(Direction::Asc), Direction::Asc),
+            ProfileSort::CreatedAtDesc => {
+                (user::created_at::order(Direction::Desc), Direction::Desc)
+            }
+        };
+
+        let users = tenant
+            .users()
+            .find_many(filters)
+            .order_by(order)
+            .order_by(user::id::order(direction))
+            .take(page.take())
+            .exec()
+            .await?;
+
+        let page = Page::from_rows(users, &page, |user| match sort {
+            ProfileSort::Username | ProfileSort::UsernameDesc => {
+                Cursor::new(ProfileKey::Username(user.username.clone()), user.id)
+            }
+            ProfileSort::CreatedAt | ProfileSort::CreatedAtDesc => {
+                Cursor::new(ProfileKey::CreatedAt(user.created_at), user.id)
+            }
+        });
+        let profiles = Self::to_profiles(&prisma, auth_user.user_id, page.items).await?;
+
+        Ok(Json::from(ProfilesPageBody {
+            profiles,
+            next_cursor: page.next_cursor,
+        }))
+    }
+
     pub async fn get_profile(
         Path(username): Path<String>,
-        auth_user: OptionalAuthUser,
+        viewer: AuthUser,
+        tenant: TenantClient,
         prisma: Prisma,
     ) -> Result<Json<ProfileBody<Profile>>, AppError> {
-        let user = prisma
-            .user()
-            .find_unique(user::username::equals(username))
-            .exec()
+        let user = Self::find_user(&tenant, username).await?;
+
+        let following = !Self::followed_among(&prisma, viewer.user_id, vec![user.id])
             .await?
-            .ok_or(AppError::NotFound(String::from("User not found")))?;
+            .is_empty();
+        let current_assignment = EmployersService::current_assignment(&tenant, user.id).await?;
+
+        let mut profile = user.to_profile(following);
+        if let Some(assignment) = current_assignment {
+            profile.set_current_assignment(assignment);
+        }
+
+        Ok(Json::from(ProfileBody { profile }))
+    }
+
+    /// Following is idempotent, following someone twice is not an error.
+    pub async fn follow(
+        Path(username): Path<String>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<ProfileBody<Profile>>, AppError> {
+        let user = Self::find_user(&tenant, username).await?;
+
+        if user.id == auth_user.user_id {
+            return Err(AppError::BadRequest(String::from(
+                "You cannot follow yourself",
+            )));
+        }
+
+        let created = prisma
+            .follow()
+            .create(
+                user::id::equals(auth_user.user_id),
+                user::id::equals(user.id),
+                vec![],
+            )
+            .observed()
+            .exec()
+            .await;
+
+        match created {
+            Ok(_) => {}
+            Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => {}
+            Err(e) => return Err(e.into()),
+        }
 
         Ok(Json::from(ProfileBody {
-                profile: user.to_profile(false),
-            }))
+            profile: user.to_profile(true),
+        }))
+    }
+
+    pub async fn unfollow(
+        Path(username): Path<String>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<ProfileBody<Profile>>, AppError> {
+        let user = Self::find_user(&tenant, username).await?;
+
+        prisma
+            .follow()
+            .delete_many(vec![
+                follow::follower_id::equals(auth_user.user_id),
+                follow::followee_id::equals(user.id),
+            ])
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(Json::from(ProfileBody {
+            profile: user.to_profile(false),
+        }))
+    }
+
+    /// Members of the organization who follow `username`, most recent first.
+    pub async fn list_followers(
+        Path(username): Path<String>,
+        Query(query): Query<FollowListQuery>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<ProfilesBody<Profile>>, AppError> {
+        let user = Self::find_user(&tenant, username).await?;
+
+        let filters = || vec![follow::followee_id::equals(user.id)];
+        let profiles_count = prisma
+            .follow()
+            .count(Self::follow_filters(&tenant, filters(), Side::Follower))
+            .observed()
+            .exec()
+            .await?;
+        let follows = prisma
+            .follow()
+            .find_many(Self::follow_filters(&tenant, filters(), Side::Follower))
+            .with(follow::follower::fetch())
+            .order_by(follow::id::order(Direction::Desc))
+            .skip(query.offset.unwrap_or(0).max(0))
+            .take(clamp_limit(query.limit))
+            .observed()
+            .exec()
+            .await?;
+
+        let users = follows
+            .into_iter()
+            .filter_map(|follow| follow.follower.map(|user| *user))
+            .collect();
+        let profiles = Self::to_profiles(&prisma, auth_user.user_id, users).await?;
+
+        Ok(Json::from(ProfilesBody {
+            profiles,
+            profiles_count,
+        }))
     }
+
+    /// Members of the organization `username` follows, most recent first.
+    pub async fn list_following(
+        Path(username): Path<String>,
+        Query(query): Query<FollowListQuery>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        prisma: Prisma,
+    ) -> Result<Json<ProfilesBody<Profile>>, AppError> {
+        let user = Self::find_user(&tenant, username).await?;
+
+        let filters = || vec![follow::follower_id::equals(user.id)];
+        let profiles_count = prisma
+            .follow()
+            .count(Self::follow_filters(&tenant, filters(), Side::Followee))
+            .observed()
+            .exec()
+            .await?;
+        let follows = prisma
+            .follow()
+            .find_many(Self::follow_filters(&tenant, filters(), Side::Followee))
+            .with(follow::followee::fetch())
+            .order_by(follow::id::order(Direction::Desc))
+            .skip(query.offset.unwrap_or(0).max(0))
+            .take(clamp_limit(query.limit))
+            .observed()
+            .exec()
+            .await?;
+
+        let users = follows
+            .into_iter()
+            .filter_map(|follow| follow.followee.map(|user| *user))
+            .collect();
+        let profiles = Self::to_profiles(&prisma, auth_user.user_id, users).await?;
+
+        Ok(Json::from(ProfilesBody {
+            profiles,
+            profiles_count,
+        }))
+    }
+
+    /// Daily hours and piece-rate output of a worker. Visible to the worker
+    /// themselves and to supervisors.
+    pub async fn get_work_history(
+        Path(username): Path<String>,
+        Query(query): Query<WorkHistoryQuery>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+    ) -> Result<Json<WorkHistoryBody<WorkHistoryDay>>, AppError> {
+        let user = Self::find_user(&tenant, username).await?;
+
+        if user.id != auth_user.user_id && !auth_user.has_role(roles::Supervisor::ALLOWED) {
+            return Err(AppError::Forbidden(String::from(
+                "Only supervisors can view other workers' history",
+            )));
+        }
+
+        let (from, to) = date_range(query.from, query.to, HISTORY_DEFAULT_DAYS, HISTORY_MAX_DAYS)?;
+
+        let work_logs = tenant
+            .work_logs()
+            .find_many(vec![
+                work_log::worker_id::equals(user.id),
+                work_log::start_time::gte(from),
+                work_log::start_time::lt(to),
+            ])
+            .exec()
+            .await?;
+
+        let tallies = tenant
+            .harvest_tallies()
+            .find_many(vec![
+                harvest_tally::worker_id::equals(user.id),
+                harvest_tally::recorded_at::gte(from),
+                harvest_tally::recorded_at::lt(to),
+            ])
+            .exec()
+            .await?;
+
+        let mut days: BTreeMap<_, WorkHistoryDay> = BTreeMap::new();
+
+        for log in &work_logs {
+            let date = log.start_time.naive_utc().date();
+            let day = days
+                .entry(date)
+                .or_insert_with(|| WorkHistoryDay::new(date));
+            day.shifts += 1;
+            day.worked_minutes += log.worked_minutes().unwrap_or(0);
+        }
+
+        for total in HarvestService::daily_totals(&tallies) {
+            let day = days
+                .entry(total.date)
+                .or_insert_with(|| WorkHistoryDay::new(total.date));
+            day.output.push(PieceOutput {
+                crop: total.crop,
+                unit: total.unit,
+                quantity: total.quantity,
+            });
+        }
+
+        Ok(Json::from(WorkHistoryBody {
+            work_history: days.into_values().rev().collect(),
+        }))
+    }
+
+    fn directory_filters(tenant: &TenantClient, query: &ProfileListQuery) -> Vec<user::WhereParam> {
+        let mut filters = vec![];
+
+        if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            filters.push(or(vec![
+                user::username::contains(q.to_string()),
+                user::first_name::contains(q.to_string()),
+                user::last_name::contains(q.to_string()),
+            ]));
+        }
+        if let Some(verified) = query.verified {
+            filters.push(user::verified::equals(verified));
+        }
+        if let Some(role) = query.role {
+            filters.push(user::memberships::some(vec![
+                membership::organization_id::equals(tenant.org_id()),
+                membership::role::equals(role),
+            ]));
+        }
+        if let Some(employer_id) = query.employer_id {
+            let now: DateTime<FixedOffset> = chrono::Utc::now().into();
+            filters.push(user::assignments::some(vec![
+                assignment::organization_id::equals(tenant.org_id()),
+                assignment::employer_id::equals(employer_id),
+                assignment::start_date::lte(now),
+                or(vec![
+                    assignment::end_date::equals(None),
+                    assignment::end_date::gte(now),
+                ]),
+            ]));
+        }
+
+        filters
+    }
+
+    /// Rows strictly after `cursor` in the order of `sort`.
+    fn after(sort: ProfileSort, cursor: &Cursor<ProfileKey>) -> Result<user::WhereParam, AppError> {
+        let id = cursor.id;
+        let filter = match (sort, &cursor.key) {
+            (ProfileSort::Username, ProfileKey::Username(username)) => or(vec![
+                user::username::gt(username.clone()),
+                and(vec![
+                    user::username::equals(username.clone()),
+                    user::id::gt(id),
+                ]),
+            ]),
+            (ProfileSort::UsernameDesc, ProfileKey::Username(username)) => or(vec![
+                user::username::lt(username.clone()),
+                and(vec![
+                    user::username::equals(username.clone()),
+                    user::id::lt(id),
+                ]),
+            ]),
+            (ProfileSort::CreatedAt, ProfileKey::CreatedAt(created_at)) => or(vec![
+                user::created_at::gt(*created_at),
+                and(vec![
+                    user::created_at::equals(*created_at),
+                    user::id::gt(id),
+                ]),
+            ]),
+            (ProfileSort::CreatedAtDesc, ProfileKey::CreatedAt(created_at)) => or(vec![
+                user::created_at::lt(*created_at),
+                and(vec![
+                    user::created_at::equals(*created_at),
+                    user::id::lt(id),
+                ]),
+            ]),
+            _ => {
+                return Err(AppError::BadRequest(String::from(
+                    "The cursor belongs to another sort order",
+                )))
+            }
+        };
+
+        Ok(filter)
+    }
+
+    async fn find_user(tenant: &TenantClient, username: String) -> Result<user::Data, AppError> {
+        tenant
+            .users()
+            .find_first(vec![user::username::equals(username)])
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("User not found")))
+    }
+
+    /// Follows are not tied to an organization, so lists only show the other
+    /// side if they are an active member of the current one.
+    fn follow_filters(
+        tenant: &TenantClient,
+        mut filters: Vec<follow::WhereParam>,
+        side: Side,
+    ) -> Vec<follow::WhereParam> {
+        let member = vec![
+            user::memberships::some(vec![membership::organization_id::equals(tenant.org_id())]),
+            user::deleted_at::equals(None),
+        ];
+        filters.push(match side {
+            Side::Follower => follow::follower::is(member),
+            Side::Followee => follow::followee::is(member),
+        });
+        filters
+    }
+
+    /// Which of `user_ids` the viewer follows.
+    async fn followed_among(
+        prisma: &PrismaClient,
+        viewer_id: i32,
+        user_ids: Vec<i32>,
+    ) -> Result<HashSet<i32>, AppError> {
+        let followed = prisma
+            .follow()
+            .find_many(vec![
+                follow::follower_id::equals(viewer_id),
+                follow::followee_id::in_vec(user_ids),
+            ])
+            .observed()
+            .exec()
+            .await?
+            .into_iter()
+            .map(|follow| follow.followee_id)
+            .collect();
+
+        Ok(followed)
+    }
+
+    async fn to_profiles(
+        prisma: &PrismaClient,
+        viewer_id: i32,
+        users: Vec<user::Data>,
+    ) -> Result<Vec<Profile>, AppError> {
+        let followed = Self::followed_among(
+            prisma,
+            viewer_id,
+            users.iter().map(|user| user.id).collect(),
+        )
+        .await?;
+
+        Ok(users
+            .into_iter()
+            .map(|user| {
+                let following = followed.contains(&user.id);
+                user.to_profile(following)
+            })
+            .collect())
+    }
+}
+
+/// Which user of a follow a list shows.
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    Follower,
+    Followee,
 }
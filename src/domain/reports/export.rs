@@ -0,0 +1,147 @@
+use crate::prisma::{work_log, WorkLogStatus};
+
+pub const COLUMNS: [&str; 9] = [
+    "Date",
+    "Worker",
+    "Employer",
+    "Job type",
+    "Start",
+    "End",
+    "Break minutes",
+    "Worked hours",
+    "Status",
+];
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Leading characters that make Excel and LibreOffice read a cell as a
+/// formula.
+const FORMULA_PREFIXES: [char; 4] = ['=', '+', '-', '@'];
+
+/// One work log as it appears in an export. Times are in UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportRow {
+    pub date: String,
+    pub worker: String,
+    pub employer: String,
+    pub job_type: String,
+    pub start: String,
+    pub end: String,
+    pub break_minutes: i32,
+    /// `None` while the shift is open.
+    pub worked_hours: Option<f64>,
+    pub status: &'static str,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ReportTotals {
+    pub entries: i64,
+    pub break_minutes: i64,
+    pub worked_minutes: i64,
+}
+
+impl ReportRow {
+    /// Expects the worker and employer relations to be fetched. Text typed
+    /// in by users is passed through [`escape_formula`].
+    pub fn from_work_log(data: &work_log::Data) -> Self {
+        Self {
+            date: data.start_time.naive_utc().date().to_string(),
+            worker: data
+                .worker()
+                .map(|worker| escape_formula(&worker.username))
+                .unwrap_or_default(),
+            employer: data
+                .employer()
+                .map(|employer| escape_formula(&employer.name))
+                .unwrap_or_default(),
+            job_type: escape_formula(&data.job_type),
+            start: data.start_time.naive_utc().format(TIME_FORMAT).to_string(),
+            end: data
+                .end_time
+                .map(|end_time| end_time.naive_utc().format(TIME_FORMAT).to_string())
+                .unwrap_or_default(),
+            break_minutes: data.break_minutes,
+            worked_hours: data.worked_minutes().map(hours),
+            status: status_label(data.status),
+        }
+    }
+
+    pub fn cells(&self) -> [String; 9] {
+        [
+            self.date.clone(),
+            self.worker.clone(),
+            self.employer.clone(),
+            self.job_type.clone(),
+            self.start.clone(),
+            self.end.clone(),
+            self.break_minutes.to_string(),
+            self.worked_hours
+                .map(|hours| format!("{:.2}", hours))
+                .unwrap_or_default(),
+            self.status.to_string(),
+        ]
+    }
+}
+
+impl ReportTotals {
+    pub fn add(&mut self, data: &work_log::Data) {
+        self.entries += 1;
+        self.break_minutes += data.break_minutes as i64;
+        self.worked_minutes += data.worked_minutes().unwrap_or(0);
+    }
+
+    pub fn worked_hours(&self) -> f64 {
+        hours(self.worked_minutes)
+    }
+
+    /// The closing row, aligned with [`COLUMNS`].
+    pub fn cells(&self) -> [String; 9] {
+        [
+            String::from("Total"),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            self.break_minutes.to_string(),
+            format!("{:.2}", self.worked_hours()),
+            format!("{} entries", self.entries),
+        ]
+    }
+}
+
+/// Encodes one CSV record, quoting fields as needed.
+pub fn csv_record<I, T>(cells: I) -> Result<Vec<u8>, csv::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(cells)?;
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
+
+/// Prefixes `'` to a cell that a spreadsheet would otherwise evaluate, so
+/// e.g. a job type of `=HYPERLINK(...)` is shown as typed.
+pub fn escape_formula(cell: &str) -> String {
+    if cell.starts_with(FORMULA_PREFIXES) {
+        format!("'{}", cell)
+    } else {
+        cell.to_string()
+    }
+}
+
+fn hours(minutes: i64) -> f64 {
+    (minutes as f64 / 60.0 * 100.0).round() / 100.0
+}
+
+fn status_label(status: WorkLogStatus) -> &'static str {
+    match status {
+        WorkLogStatus::Submitted => "Submitted",
+        WorkLogStatus::Approved => "Approved",
+        WorkLogStatus::Rejected => "Rejected",
+        WorkLogStatus::Amended => "Amended",
+    }
+}
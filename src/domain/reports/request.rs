@@ -0,0 +1,13 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+/// Whose entries to export. Without `username` or `employerId` the report
+/// covers the caller's own entries.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkHistoryReportQuery {
+    pub username: Option<String>,
+    pub employer_id: Option<i32>,
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+}
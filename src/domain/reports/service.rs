@@ -0,0 +1,337 @@
+use axum::{
+    body::Body,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::{stream, StreamExt};
+use prisma_client_rust::{
+    chrono::{DateTime, FixedOffset},
+    operator::{and, or},
+    Direction,
+};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::io::{self, Seek, SeekFrom};
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    app_error::AppError,
+    extractor::{roles, AuthUser, Query, RoleRequirement},
+    pagination::date_range,
+    prisma::{
+        user,
+        work_log::{self, WhereParam},
+    },
+    tenant::TenantClient,
+};
+
+use super::{
+    export::{self, ReportRow, ReportTotals, COLUMNS},
+    request::WorkHistoryReportQuery,
+};
+
+const PAGE_SIZE: i64 = 500;
+const DEFAULT_RANGE_DAYS: i64 = 31;
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// Which work logs a report covers.
+#[derive(Debug, Clone, Copy)]
+struct Scope {
+    worker_id: Option<i32>,
+    employer_id: Option<i32>,
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+}
+
+/// Reads a report's work logs in pages ordered by start time, so exports
+/// never hold more than one page in memory.
+struct Pages {
+    tenant: TenantClient,
+    scope: Scope,
+    after: Option<(DateTime<FixedOffset>, i32)>,
+}
+
+impl Pages {
+    async fn next(&mut self) -> Result<Vec<work_log::Data>, AppError> {
+        let mut filters = self.scope.filters();
+        if let Some((start_time, id)) = self.after {
+            filters.push(or(vec![
+                work_log::start_time::gt(start_time),
+                and(vec![
+                    work_log::start_time::equals(start_time),
+                    work_log::id::gt(id),
+                ]),
+            ]));
+        }
+
+        let page = self
+            .tenant
+            .work_logs()
+            .find_many(filters)
+            .with(work_log::worker::fetch())
+            .with(work_log::employer::fetch())
+            .order_by(work_log::start_time::order(Direction::Asc))
+            .order_by(work_log::id::order(Direction::Asc))
+            .take(PAGE_SIZE)
+            .exec()
+            .await?;
+
+        self.after = page.last().map(|log| (log.start_time, log.id));
+
+        Ok(page)
+    }
+}
+
+impl Scope {
+    fn filters(&self) -> Vec<WhereParam> {
+        let mut filters = vec![
+            work_log::start_time::gte(self.from),
+            work_log::start_time::lt(self.to),
+        ];
+        if let Some(worker_id) = self.worker_id {
+            filters.push(work_log::worker_id::equals(worker_id));
+        }
+        if let Some(employer_id) = self.employer_id {
+            filters.push(work_log::employer_id::equals(employer_id));
+        }
+        filters
+    }
+
+    fn file_name(&self, extension: &str) -> String {
+        format!(
+            "work-history-{}-{}.{}",
+            self.from.naive_utc().date(),
+            self.to.naive_utc().date(),
+            extension
+        )
+    }
+}
+
+pub struct ReportsService;
+
+impl ReportsService {
+    /// Streams the report as CSV, one database page at a time. The totals
+    /// row comes last. A database error after the first rows have been sent
+    /// can only abort the response, which leaves the download truncated.
+    pub async fn work_history_csv(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        Query(query): Query<WorkHistoryReportQuery>,
+    ) -> Result<Response, AppError> {
+        let scope = Self::scope(&tenant, &auth_user, query).await?;
+        let file_name = scope.file_name("csv");
+
+        let header = export::csv_record(COLUMNS).map_err(anyhow::Error::new)?;
+        let pages = Pages {
+            tenant,
+            scope,
+            after: None,
+        };
+
+        let rows = stream::try_unfold(Some((pages, ReportTotals::default())), |state| async move {
+            let (mut pages, mut totals) = match state {
+                Some(state) => state,
+                None => return Ok(None),
+            };
+
+            let page = pages.next().await.map_err(Self::stream_error)?;
+            if page.is_empty() {
+                let record = export::csv_record(totals.cells())?;
+                return Ok(Some((record, None)));
+            }
+
+            let mut chunk = vec![];
+            for log in &page {
+                totals.add(log);
+                chunk.extend(export::csv_record(ReportRow::from_work_log(log).cells())?);
+            }
+
+            Ok::<_, io::Error>(Some((chunk, Some((pages, totals)))))
+        });
+
+        let body = stream::iter([Ok::<_, io::Error>(header)]).chain(rows);
+
+        Ok((
+            [
+                (
+                    header::CONTENT_TYPE,
+                    String::from("text/csv; charset=utf-8"),
+                ),
+                (header::CONTENT_DISPOSITION, Self::attachment(&file_name)),
+            ],
+            Body::from_stream(body),
+        )
+            .into_response())
+    }
+
+    /// Writes the report with a constant-memory worksheet, which flushes each
+    /// row to a temporary file, then streams the finished workbook.
+    pub async fn work_history_xlsx(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        Query(query): Query<WorkHistoryReportQuery>,
+    ) -> Result<Response, AppError> {
+        let scope = Self::scope(&tenant, &auth_user, query).await?;
+        let file_name = scope.file_name("xlsx");
+
+        let mut workbook = Workbook::new();
+        let bold = Format::new().set_bold();
+        let hours = Format::new().set_num_format("0.00");
+
+        let worksheet = workbook.add_worksheet_with_constant_memory();
+        worksheet
+            .set_name("Work history")
+            .map_err(Self::xlsx_error)?;
+        worksheet
+            .write_row_with_format(0, 0, COLUMNS, &bold)
+            .map_err(Self::xlsx_error)?;
+
+        let mut pages = Pages {
+            tenant,
+            scope,
+            after: None,
+        };
+        let mut totals = ReportTotals::default();
+        let mut row = 1;
+
+        loop {
+            let page = pages.next().await?;
+            if page.is_empty() {
+                break;
+            }
+
+            for log in &page {
+                totals.add(log);
+                let report_row = ReportRow::from_work_log(log);
+                Self::write_row(worksheet, row, &report_row, &hours).map_err(Self::xlsx_error)?;
+                row += 1;
+            }
+        }
+
+        worksheet
+            .write_string_with_format(row, 0, "Total", &bold)
+            .and_then(|worksheet| {
+                worksheet.write_number_with_format(row, 6, totals.break_minutes as f64, &bold)
+            })
+            .and_then(|worksheet| {
+                worksheet.write_number_with_format(row, 7, totals.worked_hours(), &bold)
+            })
+            .and_then(|worksheet| {
+                worksheet.write_string_with_format(
+                    row,
+                    8,
+                    format!("{} entries", totals.entries),
+                    &bold,
+                )
+            })
+            .map_err(Self::xlsx_error)?;
+
+        let file = tokio::task::spawn_blocking(move || -> Result<std::fs::File, AppError> {
+            let mut file = tempfile::tempfile().map_err(anyhow::Error::new)?;
+            workbook
+                .save_to_writer(&mut file)
+                .map_err(Self::xlsx_error)?;
+            file.seek(SeekFrom::Start(0)).map_err(anyhow::Error::new)?;
+            Ok(file)
+        })
+        .await
+        .map_err(anyhow::Error::new)??;
+
+        Ok((
+            [
+                (
+                    header::CONTENT_TYPE,
+                    String::from(
+                        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                    ),
+                ),
+                (header::CONTENT_DISPOSITION, Self::attachment(&file_name)),
+            ],
+            Body::from_stream(ReaderStream::new(tokio::fs::File::from_std(file))),
+        )
+            .into_response())
+    }
+
+    /// Workers export their own entries. Supervisors may name any worker,
+    /// and only they can export an employer's entries.
+    async fn scope(
+        tenant: &TenantClient,
+        auth_user: &AuthUser,
+        query: WorkHistoryReportQuery,
+    ) -> Result<Scope, AppError> {
+        let is_supervisor = auth_user.has_role(roles::Supervisor::ALLOWED);
+
+        let worker_id = match query.username {
+            Some(username) => {
+                let worker = tenant
+                    .users()
+                    .find_first(vec![user::username::equals(username)])
+                    .exec()
+                    .await?
+                    .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+                if worker.id != auth_user.user_id && !is_supervisor {
+                    return Err(AppError::Forbidden(String::from(
+                        "Only supervisors can export other workers' history",
+                    )));
+                }
+
+                Some(worker.id)
+            }
+            None if query.employer_id.is_some() => None,
+            None => Some(auth_user.user_id),
+        };
+
+        let employer_id = match query.employer_id {
+            Some(employer_id) => {
+                if !is_supervisor {
+                    return Err(AppError::Forbidden(String::from(
+                        "Only supervisors can export an employer's history",
+                    )));
+                }
+                Some(tenant.employers().find_by_id(employer_id).await?.id)
+            }
+            None => None,
+        };
+
+        let (from, to) = date_range(query.from, query.to, DEFAULT_RANGE_DAYS, MAX_RANGE_DAYS)?;
+
+        Ok(Scope {
+            worker_id,
+            employer_id,
+            from,
+            to,
+        })
+    }
+
+    fn write_row(
+        worksheet: &mut rust_xlsxwriter::Worksheet,
+        row: u32,
+        report_row: &ReportRow,
+        hours: &Format,
+    ) -> Result<(), XlsxError> {
+        worksheet.write_string(row, 0, &report_row.date)?;
+        worksheet.write_string(row, 1, &report_row.worker)?;
+        worksheet.write_string(row, 2, &report_row.employer)?;
+        worksheet.write_string(row, 3, &report_row.job_type)?;
+        worksheet.write_string(row, 4, &report_row.start)?;
+        worksheet.write_string(row, 5, &report_row.end)?;
+        worksheet.write_number(row, 6, report_row.break_minutes)?;
+        if let Some(worked_hours) = report_row.worked_hours {
+            worksheet.write_number_with_format(row, 7, worked_hours, hours)?;
+        }
+        worksheet.write_string(row, 8, report_row.status)?;
+        Ok(())
+    }
+
+    fn attachment(file_name: &str) -> String {
+        format!("attachment; filename=\"{}\"", file_name)
+    }
+
+    fn stream_error(error: AppError) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, error.to_string())
+    }
+
+    fn xlsx_error(error: XlsxError) -> AppError {
+        AppError::Anyhow(anyhow::Error::new(error).context("failed to write spreadsheet"))
+    }
+}
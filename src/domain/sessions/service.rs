@@ -0,0 +1,298 @@
+use prisma_client_rust::{
+    chrono::{self, DateTime, Duration, FixedOffset},
+    Direction,
+};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{
+    app_error::AppError,
+    config::AppContext,
+    extractor::AuthUser,
+    prisma::{membership, organization, session, user, PrismaClient},
+    telemetry::Observe,
+};
+
+pub struct SessionsService;
+
+impl SessionsService {
+    /// Opens a new session for `user` and returns the authenticated user for
+    /// it together with the plain refresh token. Only a hash of the refresh
+    /// token is stored.
+    ///
+    /// The session is bound to `requested_org` when given, which the user must
+    /// be a member of, otherwise to their first organization if they have any.
+    pub async fn create(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        user: &user::Data,
+        requested_org: Option<i32>,
+    ) -> Result<(AuthUser, String), AppError> {
+        let membership = Self::resolve_membership(prisma, user.id, requested_org, None).await?;
+        Self::open(prisma, ctx, user, membership).await
+    }
+
+    /// Exchanges a refresh token for a new session. The old session is revoked,
+    /// and presenting an already rotated token revokes every session of the
+    /// user since it means the token has leaked.
+    ///
+    /// The new session keeps the organization of the old one unless another
+    /// one is requested.
+    pub async fn rotate(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        refresh_token: &str,
+        requested_org: Option<i32>,
+    ) -> Result<(user::Data, AuthUser, String), AppError> {
+        let invalid = || AppError::Unauthorized(String::from("Invalid refresh token"));
+
+        let current = prisma
+            .session()
+            .find_unique(session::refresh_token_hash::equals(
+                Self::hash_refresh_token(refresh_token),
+            ))
+            .with(session::user::fetch())
+            .observed()
+            .exec()
+            .await?
+            .ok_or_else(invalid)?;
+
+        if current.revoked_at.is_some() {
+            if current.replaced_by_id.is_some() {
+                warn!(
+                    "Refresh token reuse detected for user {}, revoking all sessions",
+                    current.user_id
+                );
+                Self::revoke_all(prisma, current.user_id).await?;
+            }
+            return Err(invalid());
+        }
+
+        if current.expires_at < Self::now() {
+            return Err(invalid());
+        }
+
+        // Only one caller may win the rotation of a given token.
+        let revoked = prisma
+            .session()
+            .update_many(
+                vec![
+                    session::id::equals(current.id),
+                    session::revoked_at::equals(None),
+                ],
+                vec![session::revoked_at::set(Some(Self::now()))],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        if revoked == 0 {
+            return Err(invalid());
+        }
+
+        let user = current.user().map_err(|_| invalid())?.clone();
+        if user.deleted_at.is_some() {
+            return Err(invalid());
+        }
+        let membership =
+            Self::resolve_membership(prisma, user.id, requested_org, current.organization_id)
+                .await?;
+        let (auth_user, refresh_token) = Self::open(prisma, ctx, &user, membership).await?;
+
+        prisma
+            .session()
+            .update(
+                session::id::equals(current.id),
+                vec![session::replaced_by_id::set(Some(auth_user.session_id))],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok((user, auth_user, refresh_token))
+    }
+
+    pub async fn revoke(prisma: &PrismaClient, session_id: i32) -> Result<(), AppError> {
+        prisma
+            .session()
+            .update_many(
+                vec![
+                    session::id::equals(session_id),
+                    session::revoked_at::equals(None),
+                ],
+                vec![session::revoked_at::set(Some(Self::now()))],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_all(prisma: &PrismaClient, user_id: i32) -> Result<(), AppError> {
+        prisma
+            .session()
+            .update_many(
+                vec![
+                    session::user_id::equals(user_id),
+                    session::revoked_at::equals(None),
+                ],
+                vec![session::revoked_at::set(Some(Self::now()))],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Revokes the user's sessions in one organization, e.g. after their
+    /// membership changed.
+    pub async fn revoke_for_organization(
+        prisma: &PrismaClient,
+        user_id: i32,
+        org_id: i32,
+    ) -> Result<(), AppError> {
+        prisma
+            .session()
+            .update_many(
+                vec![
+                    session::user_id::equals(user_id),
+                    session::organization_id::equals(Some(org_id)),
+                    session::revoked_at::equals(None),
+                ],
+                vec![session::revoked_at::set(Some(Self::now()))],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether an access token issued for `session_id` may still be used.
+    /// Tokens of deleted users are refused even if a session survived.
+    pub async fn is_active(
+        prisma: &PrismaClient,
+        session_id: i32,
+        user_id: i32,
+    ) -> Result<bool, AppError> {
+        let session = prisma
+            .session()
+            .find_first(vec![
+                session::id::equals(session_id),
+                session::user_id::equals(user_id),
+                session::revoked_at::equals(None),
+                session::expires_at::gt(Self::now()),
+                session::user::is(vec![user::deleted_at::equals(None)]),
+            ])
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(session.is_some())
+    }
+
+    async fn open(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        user: &user::Data,
+        membership: Option<membership::Data>,
+    ) -> Result<(AuthUser, String), AppError> {
+        let refresh_token = Self::generate_refresh_token();
+        let expires_at =
+            Self::now() + Duration::seconds(ctx.config.jwt.refresh_exp.as_secs() as i64);
+
+        let mut params = vec![];
+        if let Some(membership) = &membership {
+            params.push(session::organization::connect(organization::id::equals(
+                membership.organization_id,
+            )));
+        }
+
+        let session = prisma
+            .session()
+            .create(
+                user::id::equals(user.id),
+                Self::hash_refresh_token(&refresh_token),
+                expires_at,
+                params,
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        let auth_user = AuthUser {
+            user_id: user.id,
+            role: membership
+                .as_ref()
+                .map_or(user.role, |membership| membership.role),
+            session_id: session.id,
+            org_id: membership.map(|membership| membership.organization_id),
+        };
+
+        Ok((auth_user, refresh_token))
+    }
+
+    /// An explicitly requested organization must be one the user belongs to.
+    /// Otherwise `preferred` is used if the user is still a member of it, and
+    /// then their oldest membership.
+    async fn resolve_membership(
+        prisma: &PrismaClient,
+        user_id: i32,
+        requested: Option<i32>,
+        preferred: Option<i32>,
+    ) -> Result<Option<membership::Data>, AppError> {
+        if let Some(org_id) = requested {
+            let membership = prisma
+                .membership()
+                .find_unique(membership::user_id_organization_id(user_id, org_id))
+                .observed()
+                .exec()
+                .await?
+                .ok_or(AppError::Forbidden(String::from(
+                    "You are not a member of this organization",
+                )))?;
+
+            return Ok(Some(membership));
+        }
+
+        if let Some(org_id) = preferred {
+            let membership = prisma
+                .membership()
+                .find_unique(membership::user_id_organization_id(user_id, org_id))
+                .observed()
+                .exec()
+                .await?;
+
+            if membership.is_some() {
+                return Ok(membership);
+            }
+        }
+
+        let membership = prisma
+            .membership()
+            .find_first(vec![membership::user_id::equals(user_id)])
+            .order_by(membership::id::order(Direction::Asc))
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(membership)
+    }
+
+    fn generate_refresh_token() -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    fn hash_refresh_token(refresh_token: &str) -> String {
+        hex::encode(Sha256::digest(refresh_token.as_bytes()))
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
+}
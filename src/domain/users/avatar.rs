@@ -0,0 +1,60 @@
+use image::{
+    imageops::FilterType,
+    io::{Limits, Reader},
+    ImageFormat,
+};
+use std::io::Cursor;
+
+/// Larger uploads are rejected before decoding.
+pub const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+
+/// Edge lengths of the square thumbnails kept for every avatar. `User.image`
+/// points at the first one.
+pub const AVATAR_SIZES: [u32; 2] = [256, 64];
+
+/// Guards the decoder against small files that claim huge dimensions.
+const MAX_DIMENSION: u32 = 8_192;
+
+#[derive(Debug)]
+pub struct Thumbnail {
+    pub size: u32,
+    pub png: Vec<u8>,
+}
+
+/// Checks an uploaded avatar and renders its thumbnails as PNG. The format
+/// is sniffed from the content; the declared content type is not trusted.
+pub fn thumbnails(bytes: &[u8]) -> Result<Vec<Thumbnail>, &'static str> {
+    if bytes.len() > MAX_AVATAR_BYTES {
+        return Err("Avatar must be at most 5 MB");
+    }
+
+    let format = match image::guess_format(bytes) {
+        Ok(format @ (ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP)) => format,
+        _ => return Err("Avatar must be a PNG, JPEG or WebP image"),
+    };
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = Reader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|_| "Avatar could not be read as an image")?;
+
+    AVATAR_SIZES
+        .iter()
+        .map(|&size| {
+            // Crops to the centre square, then scales
+            let thumbnail = image.resize_to_fill(size, size, FilterType::Lanczos3);
+
+            let mut png = Vec::new();
+            thumbnail
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(|_| "Avatar could not be resized")?;
+
+            Ok(Thumbnail { size, png })
+        })
+        .collect()
+}
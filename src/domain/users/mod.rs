@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::validation::{Validate, ValidationErrors};
+
+pub mod avatar;
 pub mod request;
 pub mod response;
 pub mod service;
@@ -8,3 +11,9 @@ pub mod service;
 pub struct UserBody<T> {
     pub user: T,
 }
+
+impl<T: Validate> Validate for UserBody<T> {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        self.user.validate(errors);
+    }
+}
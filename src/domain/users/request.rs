@@ -1,5 +1,10 @@
 use serde::Deserialize;
 
+use crate::{
+    prisma::Role,
+    validation::{rules, Validate, ValidationErrors},
+};
+
 #[derive(Debug, Deserialize)]
 pub struct UserCreateInput {
     pub username: String,
@@ -20,3 +25,80 @@ pub struct UserLoginInput {
     pub username: String,
     pub password: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct UserRoleUpdateInput {
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRefreshInput {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserLogoutQuery {
+    pub all: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserForgotPasswordInput {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserResetPasswordInput {
+    pub email: String,
+    pub code: u32,
+    pub password: String,
+}
+
+impl Validate for UserCreateInput {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        errors.check("username", rules::username(&self.username));
+        errors.check("password", rules::password(&self.password));
+    }
+}
+
+impl Validate for UserUpdateInput {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        if let Some(email) = &self.email {
+            errors.check("email", rules::email(email));
+        }
+        if let Some(username) = &self.username {
+            errors.check("username", rules::username(username));
+        }
+        if let Some(bio) = &self.bio {
+            errors.check("bio", rules::bio(bio));
+        }
+        if let Some(image) = &self.image {
+            errors.check("image", rules::image_url(image));
+        }
+        if let Some(password) = &self.password {
+            errors.check("password", rules::password(password));
+        }
+    }
+}
+
+// Only presence is checked on login, accounts created before the password
+// rules must still be able to sign in.
+impl Validate for UserLoginInput {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        errors.check("username", rules::not_empty(&self.username));
+        errors.check("password", rules::not_empty(&self.password));
+    }
+}
+
+impl Validate for UserForgotPasswordInput {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        errors.check("email", rules::email(&self.email));
+    }
+}
+
+impl Validate for UserResetPasswordInput {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        errors.check("email", rules::email(&self.email));
+        errors.check("password", rules::password(&self.password));
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use prisma_client_rust::chrono::{FixedOffset, TimeZone};
 
-use crate::prisma::user;
+use crate::prisma::{user, Role};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,18 +12,24 @@ pub struct User {
     pub bio: Option<String>,
     pub image: Option<String>,
     pub verified: bool,
+    pub role: Role,
     pub created_at:
         ::prisma_client_rust::chrono::DateTime<FixedOffset>,
     pub updated_at:
         ::prisma_client_rust::chrono::DateTime<FixedOffset>,
 
     pub token: Option<String>,
+    pub refresh_token: Option<String>,
 }
 
 impl User {
     pub fn set_token(&mut self, token: String) {
         self.token = Some(token);
     }
+
+    pub fn set_refresh_token(&mut self, refresh_token: String) {
+        self.refresh_token = Some(refresh_token);
+    }
 }
 
 impl From<user::Data> for User {
@@ -35,9 +41,11 @@ impl From<user::Data> for User {
             bio: data.bio,
             image: data.image,
             verified: data.verified,
+            role: data.role,
             created_at: data.created_at,
             updated_at: data.updated_at,
             token: None,
+            refresh_token: None,
         }
     }
 }
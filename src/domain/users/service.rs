@@ -1,24 +1,57 @@
 use argon2::{ password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier };
-use axum::{ extract::State, Extension, Json };
-use rand::rngs::OsRng;
+use axum::{
+    body::Bytes,
+    extract::{ multipart::MultipartError, Multipart, State },
+    http::StatusCode,
+    Extension,
+};
+use prisma_client_rust::{ chrono::{ self, DateTime, Duration, FixedOffset }, operator::not };
+use rand::{ distributions::Alphanumeric, rngs::OsRng, Rng };
 use std::sync::Arc;
-use regex::Regex;
+use tracing::{ error, warn };
 
 use crate::{
-    app_error::AppError,
+    app_error::{ AppError, FieldError },
     config::AppContext,
-    extractor::AuthUser,
-    prisma::{ self, user::{ self, UniqueWhereParam, WhereParam }, PrismaClient },
+    domain::{ otp::service::OTPService, sessions::service::SessionsService },
+    extractor::{ roles, AuthUser, Json, OrgSelection, Path, Query, RequireRole, VerifiedAuthUser },
+    prisma::{
+        self,
+        membership,
+        user::{ self, UniqueWhereParam, WhereParam },
+        OtpPurpose,
+        PrismaClient,
+        Role,
+    },
+    storage::Storage,
+    telemetry::Observe,
+    tenant::TenantClient,
+    validation::{ rules, ValidatedJson },
 };
 
 use super::{
-    request::{ UserCreateInput, UserLoginInput, UserUpdateInput },
+    avatar::{ self, AVATAR_SIZES },
+    request::{
+        UserCreateInput,
+        UserForgotPasswordInput,
+        UserLoginInput,
+        UserLogoutQuery,
+        UserRefreshInput,
+        UserResetPasswordInput,
+        UserRoleUpdateInput,
+        UserUpdateInput,
+    },
     response::User,
     UserBody,
 };
 
 type Prisma = Extension<Arc<PrismaClient>>;
 
+const GENERATED_PASSWORD_LEN: usize = 16;
+const RELEASED_USERNAME_PREFIX: &str = "deleted#";
+/// Same for an unknown user and a wrong password, so usernames cannot be probed.
+const INVALID_CREDENTIALS: &str = "Invalid username or password";
+
 pub struct UsersService;
 
 impl UsersService {
@@ -30,24 +63,27 @@ impl UsersService {
         let data = prisma
             .user()
             .find_unique(user::id::equals(auth_user.user_id))
+            .observed()
             .exec().await
             .unwrap();
 
         match data {
-            Some(data) => {
+            Some(data) if data.deleted_at.is_none() => {
                 let mut user: User = data.into();
+                user.role = auth_user.role;
                 user.set_token(auth_user.to_jwt(&ctx));
 
                 Ok(Json::from(UserBody { user }))
             }
-            None => Err(AppError::NotFound(String::from("User not found"))),
+            _ => Err(AppError::NotFound(String::from("User not found"))),
         }
     }
 
     pub async fn create_user(
         prisma: Prisma,
         ctx: State<AppContext>,
-        Json(input): Json<UserBody<UserCreateInput>>
+        OrgSelection(org_id): OrgSelection,
+        ValidatedJson(input): ValidatedJson<UserBody<UserCreateInput>>
     ) -> Result<Json<UserBody<User>>, AppError> {
         let UserBody { user: UserCreateInput { password, username } } = input;
 
@@ -56,20 +92,19 @@ impl UsersService {
             let Some(existing_user) = prisma
                 .user()
                 .find_unique(user::username::equals(username_input.to_string()))
+                .observed()
                 .exec().await?
         {
-            return Err(AppError::BadRequest("Username is already taken".to_string()));
+            return Err(AppError::Conflict(String::from("Username is already taken")));
         }
 
         let data = prisma
             .user()
             .create(Self::hash_password(password.as_str()).unwrap(), username, vec![])
+            .observed()
             .exec().await?;
 
-        let token = (AuthUser { user_id: data.id }).to_jwt(&ctx);
-
-        let mut user: User = data.into();
-        user.set_token(token);
+        let user = Self::start_session(&prisma, &ctx, data, org_id).await?;
 
         Ok(Json::from(UserBody { user }))
     }
@@ -78,20 +113,24 @@ impl UsersService {
         prisma: Prisma,
         auth_user: AuthUser,
         ctx: State<AppContext>,
-        Json(input): Json<UserBody<UserUpdateInput>>
+        ValidatedJson(input): ValidatedJson<UserBody<UserUpdateInput>>
     ) -> Result<Json<UserBody<User>>, AppError> {
         let UserBody { user: UserUpdateInput { email, bio, image, username, password } } = input;
 
-        if let Some(email) = &email {
-            Self::is_valid_email(email)?;
-        }
-
         let data = prisma
             .user()
             .find_unique(user::id::equals(auth_user.user_id))
+            .observed()
             .exec().await?
             .unwrap();
 
+        // Credentials can only change once the account has an address to
+        // recover them through
+        let changes_email = email.as_ref().is_some_and(|email| data.email.as_ref() != Some(email));
+        if password.is_some() || changes_email {
+            VerifiedAuthUser::check(auth_user.clone(), data.verified)?;
+        }
+
         let data = prisma
             .user()
             .update(
@@ -105,6 +144,13 @@ impl UsersService {
                         Some(image) => user::image::set(Some(image)),
                         None => user::image::set(data.image),
                     },
+                    // A changed address has to go through OTP verification again
+                    match &email {
+                        Some(email) if data.email.as_ref() != Some(email) => {
+                            user::verified::set(false)
+                        }
+                        _ => user::verified::set(data.verified),
+                    },
                     match email {
                         Some(email) => user::email::set(Some(email)),
                         None => user::email::set(data.email),
@@ -121,12 +167,12 @@ impl UsersService {
                     }
                 ]
             )
+            .observed()
             .exec().await?;
 
         let mut user: User = data.into();
-
-        let token = (AuthUser { user_id: user.id }).to_jwt(&ctx);
-        user.set_token(token);
+        user.role = auth_user.role;
+        user.set_token(auth_user.to_jwt(&ctx));
 
         Ok(Json::from(UserBody { user }))
     }
@@ -134,37 +180,500 @@ impl UsersService {
     pub async fn login(
         prisma: Prisma,
         ctx: State<AppContext>,
-        Json(input): Json<UserBody<UserLoginInput>>
+        OrgSelection(org_id): OrgSelection,
+        ValidatedJson(input): ValidatedJson<UserBody<UserLoginInput>>
     ) -> Result<Json<UserBody<User>>, AppError> {
         let UserBody { user: UserLoginInput { username, password } } = input;
 
-        let data = prisma.user().find_unique(user::username::equals(username)).exec().await?;
+        let data = prisma
+            .user()
+            .find_unique(user::username::equals(username))
+            .observed()
+            .exec().await?;
 
         let data = match data {
-            Some(user_data) => user_data,
-            None => {
-                return Err(AppError::NotFound(String::from("User not found")));
+            Some(user_data) if user_data.deleted_at.is_none() => user_data,
+            _ => {
+                return Err(AppError::Unauthorized(String::from(INVALID_CREDENTIALS)));
             }
         };
 
         Self::verify_password(password.as_str(), data.password.as_str())?;
+        let user = Self::start_session(&prisma, &ctx, data, org_id).await?;
+
+        Ok(Json::from(UserBody { user }))
+    }
+
+    pub async fn refresh(
+        prisma: Prisma,
+        ctx: State<AppContext>,
+        OrgSelection(org_id): OrgSelection,
+        Json(input): Json<UserBody<UserRefreshInput>>
+    ) -> Result<Json<UserBody<User>>, AppError> {
+        let UserBody { user: UserRefreshInput { refresh_token } } = input;
+
+        let (data, auth_user, refresh_token) = SessionsService::rotate(
+            &prisma,
+            &ctx,
+            &refresh_token,
+            org_id
+        ).await?;
+
         let mut user: User = data.into();
+        user.role = auth_user.role;
+        user.set_token(auth_user.to_jwt(&ctx));
+        user.set_refresh_token(refresh_token);
+
+        Ok(Json::from(UserBody { user }))
+    }
 
-        let token = (AuthUser { user_id: user.id }).to_jwt(&ctx);
-        user.set_token(token);
+    pub async fn logout(
+        auth_user: AuthUser,
+        prisma: Prisma,
+        Query(query): Query<UserLogoutQuery>
+    ) -> Result<StatusCode, AppError> {
+        if query.all.unwrap_or(false) {
+            SessionsService::revoke_all(&prisma, auth_user.user_id).await?;
+        } else {
+            SessionsService::revoke(&prisma, auth_user.session_id).await?;
+        }
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    pub async fn forgot_password(
+        prisma: Prisma,
+        ctx: State<AppContext>,
+        ValidatedJson(input): ValidatedJson<UserBody<UserForgotPasswordInput>>
+    ) -> Result<(StatusCode, String), AppError> {
+        let UserBody { user: UserForgotPasswordInput { email } } = input;
+
+        // Answer the same way whether or not the email is known
+        let response = (
+            StatusCode::ACCEPTED,
+            String::from("If the email is registered, a reset code has been sent to it"),
+        );
+
+        let data = prisma
+            .user()
+            .find_first(
+                vec![
+                    user::email::equals(Some(email.clone())),
+                    user::verified::equals(true),
+                    user::deleted_at::equals(None)
+                ]
+            )
+            .observed()
+            .exec().await?;
+
+        let data = match data {
+            Some(data) => data,
+            None => {
+                return Ok(response);
+            }
+        };
+
+        let code = OTPService::issue_code(
+            &prisma,
+            &ctx,
+            data.id,
+            &email,
+            OtpPurpose::PasswordReset
+        ).await?;
+
+        // Sending happens in the background so response time does not leak
+        // whether the email exists
+        let ctx = ctx.0.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = OTPService::send_email_otp(&email, &code, &ctx) {
+                error!("Failed to send password reset email: {:?}", e);
+            }
+        });
+
+        Ok(response)
+    }
+
+    pub async fn reset_password(
+        prisma: Prisma,
+        ctx: State<AppContext>,
+        ValidatedJson(input): ValidatedJson<UserBody<UserResetPasswordInput>>
+    ) -> Result<String, AppError> {
+        let UserBody { user: UserResetPasswordInput { email, code, password } } = input;
+
+        let invalid = || AppError::BadRequest(String::from("Invalid or expired code"));
+
+        let data = prisma
+            .user()
+            .find_first(
+                vec![
+                    user::email::equals(Some(email)),
+                    user::verified::equals(true),
+                    user::deleted_at::equals(None)
+                ]
+            )
+            .observed()
+            .exec().await?
+            .ok_or_else(invalid)?;
+
+        OTPService::consume_code(
+            &prisma,
+            &ctx,
+            data.id,
+            OtpPurpose::PasswordReset,
+            code
+        ).await.map_err(|e| {
+            match e {
+                AppError::BadRequest(_) | AppError::TooManyRequests(_) => invalid(),
+                e => e,
+            }
+        })?;
+
+        prisma
+            .user()
+            .update(
+                user::id::equals(data.id),
+                vec![user::password::set(Self::hash_password(password.as_str())?)]
+            )
+            .observed()
+            .exec().await?;
+
+        SessionsService::revoke_all(&prisma, data.id).await?;
+
+        Ok("Password has been reset".to_string())
+    }
+
+    pub async fn update_role(
+        Path(username): Path<String>,
+        _admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<UserBody<UserRoleUpdateInput>>
+    ) -> Result<Json<UserBody<User>>, AppError> {
+        let UserBody { user: UserRoleUpdateInput { role } } = input;
+
+        let data = tenant
+            .users()
+            .find_first(vec![user::username::equals(username)])
+            .exec().await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        tenant
+            .memberships()
+            .update_many(vec![membership::user_id::equals(data.id)], vec![membership::role::set(role)])
+            .exec().await?;
+
+        // Tokens carry the role, make the user pick up the new one
+        SessionsService::revoke_for_organization(&prisma, data.id, tenant.org_id()).await?;
+
+        let mut user: User = data.into();
+        user.role = role;
 
         Ok(Json::from(UserBody { user }))
     }
 
-    fn is_valid_email(email: &str) -> Result<(), AppError> {
-        let email_regex = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
-        if !email_regex.is_match(email) {
-            return Err(AppError::BadRequest(String::from("Invalid email format")));
+    /// Replaces the caller's avatar with thumbnails of the image sent in the
+    /// multipart field `avatar`.
+    pub async fn upload_avatar(
+        auth_user: AuthUser,
+        ctx: State<AppContext>,
+        prisma: Prisma,
+        mut multipart: Multipart
+    ) -> Result<Json<UserBody<User>>, AppError> {
+        let mut upload = None;
+        while let Some(field) = multipart.next_field().await.map_err(Self::multipart_error)? {
+            if field.name() == Some("avatar") {
+                upload = Some(field.bytes().await.map_err(Self::multipart_error)?);
+                break;
+            }
+        }
+        let upload = upload.ok_or_else(|| Self::avatar_error("Must not be empty"))?;
+
+        // Decoding and resizing are CPU bound
+        let thumbnails = tokio::task
+            ::spawn_blocking(move || avatar::thumbnails(&upload)).await
+            .map_err(anyhow::Error::new)?
+            .map_err(Self::avatar_error)?;
+
+        let data = prisma
+            .user()
+            .find_unique(user::id::equals(auth_user.user_id))
+            .observed()
+            .exec().await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        // Every upload gets a new directory, so caches never serve the old
+        // image under the new URL
+        let dir = format!("avatars/{}/{:016x}", auth_user.user_id, OsRng.gen::<u64>());
+        for thumbnail in thumbnails {
+            ctx.storage.put(
+                &format!("{}/{}.png", dir, thumbnail.size),
+                "image/png",
+                Bytes::from(thumbnail.png)
+            ).await?;
         }
-        Ok(())
+
+        let url = ctx.storage.url(&format!("{}/{}.png", dir, AVATAR_SIZES[0]));
+        let updated = prisma
+            .user()
+            .update(user::id::equals(auth_user.user_id), vec![user::image::set(Some(url))])
+            .observed()
+            .exec().await?;
+
+        if let Some(previous) = data.image.as_deref() {
+            Self::delete_avatar(ctx.storage.as_ref(), auth_user.user_id, previous).await;
+        }
+
+        let mut user: User = updated.into();
+        user.role = auth_user.role;
+        user.set_token(auth_user.to_jwt(&ctx));
+
+        Ok(Json::from(UserBody { user }))
+    }
+
+    /// Deletes the caller's own account. It can be restored by an admin
+    /// during the grace period.
+    pub async fn delete_current_user(
+        auth_user: AuthUser,
+        prisma: Prisma
+    ) -> Result<StatusCode, AppError> {
+        Self::deactivate(&prisma, auth_user.user_id).await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Platform admins delete the account itself. Organization admins can
+    /// only take the user out of their organization, the account and its
+    /// other memberships are left alone.
+    pub async fn deactivate_user(
+        Path(username): Path<String>,
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        prisma: Prisma
+    ) -> Result<Json<UserBody<User>>, AppError> {
+        let data = tenant
+            .users()
+            .find_first(vec![user::username::equals(username)])
+            .exec().await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        if data.id == admin.user_id {
+            return Err(
+                AppError::BadRequest(String::from("Use DELETE /api/user to delete your own account"))
+            );
+        }
+
+        if Self::is_platform_admin(&prisma, admin.user_id).await? {
+            let data = Self::deactivate(&prisma, data.id).await?;
+
+            return Ok(Json::from(UserBody { user: data.into() }));
+        }
+
+        tenant
+            .memberships()
+            .delete_many(vec![membership::user_id::equals(data.id)])
+            .exec().await?;
+
+        SessionsService::revoke_for_organization(&prisma, data.id, tenant.org_id()).await?;
+
+        Ok(Json::from(UserBody { user: data.into() }))
+    }
+
+    /// Only platform admins can undo a deletion, since only they can delete
+    /// accounts through the API.
+    pub async fn restore_user(
+        Path(username): Path<String>,
+        admin: RequireRole<roles::Admin>,
+        tenant: TenantClient,
+        ctx: State<AppContext>,
+        prisma: Prisma
+    ) -> Result<Json<UserBody<User>>, AppError> {
+        if !Self::is_platform_admin(&prisma, admin.user_id).await? {
+            return Err(AppError::Forbidden(String::from("Only platform admins can restore accounts")));
+        }
+
+        // Tenant queries hide deleted users, so look them up directly
+        let data = prisma
+            .user()
+            .find_first(
+                vec![
+                    user::username::equals(username),
+                    user::deleted_at::not(None),
+                    user::memberships::some(
+                        vec![membership::organization_id::equals(tenant.org_id())]
+                    )
+                ]
+            )
+            .observed()
+            .exec().await?
+            .ok_or(AppError::NotFound(String::from("No deleted user with this username")))?;
+
+        let data = Self::restore(&prisma, &ctx, &data).await?;
+
+        Ok(Json::from(UserBody { user: data.into() }))
+    }
+
+    /// Clears the username and email of accounts deleted longer ago than the
+    /// grace period, so others can take them. Returns how many were released.
+    pub async fn release_deleted_accounts(
+        prisma: &PrismaClient,
+        ctx: &AppContext
+    ) -> Result<usize, AppError> {
+        let grace = Duration::seconds(ctx.config.accounts.deletion_grace.as_secs() as i64);
+        let cutoff = Self::now() - grace;
+
+        let expired = prisma
+            .user()
+            .find_many(
+                vec![
+                    user::deleted_at::lt(cutoff),
+                    not(vec![user::username::starts_with(RELEASED_USERNAME_PREFIX.to_string())])
+                ]
+            )
+            .observed()
+            .exec().await?;
+
+        for data in &expired {
+            prisma
+                .user()
+                .update(
+                    user::id::equals(data.id),
+                    vec![
+                        user::username::set(Self::released_username(data.id)),
+                        user::email::set(None),
+                        user::verified::set(false)
+                    ]
+                )
+                .observed()
+                .exec().await?;
+        }
+
+        Ok(expired.len())
+    }
+
+    /// Usernames may not contain '#', so a released username can never clash
+    /// with a real one.
+    pub fn released_username(user_id: i32) -> String {
+        format!("{}{}", RELEASED_USERNAME_PREFIX, user_id)
     }
 
-    fn hash_password(password: &str) -> anyhow::Result<String> {
+    /// Removes the thumbnails of a replaced avatar if it was uploaded to
+    /// storage. Failures only leave orphaned files behind, so they are logged.
+    async fn delete_avatar(storage: &dyn Storage, user_id: i32, url: &str) {
+        let prefix = format!("avatars/{}/", user_id);
+        let dir = match storage.key(url) {
+            Some(key) if key.starts_with(&prefix) => {
+                match key.rsplit_once('/') {
+                    Some((dir, _)) => dir.to_string(),
+                    None => {
+                        return;
+                    }
+                }
+            }
+            _ => {
+                return;
+            }
+        };
+
+        for size in AVATAR_SIZES {
+            let key = format!("{}/{}.png", dir, size);
+            if let Err(e) = storage.delete(&key).await {
+                warn!("Failed to delete old avatar {}: {:?}", key, e);
+            }
+        }
+    }
+
+    fn avatar_error(message: &str) -> AppError {
+        AppError::Validation(vec![FieldError::new("avatar", message)])
+    }
+
+    fn multipart_error(error: MultipartError) -> AppError {
+        AppError::BadRequest(format!("Invalid upload: {}", error.body_text()))
+    }
+
+    async fn start_session(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        data: user::Data,
+        org_id: Option<i32>
+    ) -> Result<User, AppError> {
+        let (auth_user, refresh_token) = SessionsService::create(prisma, ctx, &data, org_id).await?;
+
+        let mut user: User = data.into();
+        user.role = auth_user.role;
+        user.set_token(auth_user.to_jwt(ctx));
+        user.set_refresh_token(refresh_token);
+
+        Ok(user)
+    }
+
+    /// Soft-deletes the user and ends all their sessions. Their records
+    /// are kept.
+    pub async fn deactivate(prisma: &PrismaClient, user_id: i32) -> Result<user::Data, AppError> {
+        let data = prisma
+            .user()
+            .update(user::id::equals(user_id), vec![user::deleted_at::set(Some(Self::now()))])
+            .observed()
+            .exec().await?;
+
+        SessionsService::revoke_all(prisma, user_id).await?;
+
+        Ok(data)
+    }
+
+    /// Whether the user is an admin of the whole platform, as opposed to
+    /// an admin of the selected organization only.
+    async fn is_platform_admin(prisma: &PrismaClient, user_id: i32) -> Result<bool, AppError> {
+        let data = prisma
+            .user()
+            .find_unique(user::id::equals(user_id))
+            .observed()
+            .exec().await?
+            .ok_or(AppError::NotFound(String::from("User not found")))?;
+
+        Ok(data.role == Role::Admin)
+    }
+
+    /// Undoes a deletion. Not possible once the grace period is over, since
+    /// the username and email may have been taken by then.
+    pub async fn restore(
+        prisma: &PrismaClient,
+        ctx: &AppContext,
+        data: &user::Data
+    ) -> Result<user::Data, AppError> {
+        let grace = Duration::seconds(ctx.config.accounts.deletion_grace.as_secs() as i64);
+        if let Some(deleted_at) = data.deleted_at {
+            if deleted_at + grace < Self::now() {
+                return Err(
+                    AppError::Conflict(String::from("The account was deleted too long ago to restore"))
+                );
+            }
+        }
+
+        let data = prisma
+            .user()
+            .update(user::id::equals(data.id), vec![user::deleted_at::set(None)])
+            .observed()
+            .exec().await?;
+
+        Ok(data)
+    }
+
+    /// A random password that satisfies the password rules, for accounts
+    /// created on someone's behalf.
+    pub fn generate_password() -> String {
+        loop {
+            let password: String = OsRng.sample_iter(&Alphanumeric)
+                .take(GENERATED_PASSWORD_LEN)
+                .map(char::from)
+                .collect();
+
+            if rules::password(&password).is_ok() {
+                return password;
+            }
+        }
+    }
+
+    pub fn hash_password(password: &str) -> anyhow::Result<String> {
         let salt = SaltString::generate(&mut OsRng);
 
         // Argon2 with default params (Argon2id v19)
@@ -178,7 +687,7 @@ impl UsersService {
         Ok(password_hash.to_string())
     }
 
-    fn verify_password(password: &str, password_hash: &str) -> anyhow::Result<()> {
+    fn verify_password(password: &str, password_hash: &str) -> Result<(), AppError> {
         let argon2 = Argon2::default();
         // Parse password hash from PHC string
         let password_hash = PasswordHash::new(password_hash).map_err(|_|
@@ -187,7 +696,11 @@ impl UsersService {
         // Verify password against hash
         argon2
             .verify_password(password.as_bytes(), &password_hash)
-            .map_err(|_| anyhow::anyhow!("failed to verify password"))?;
+            .map_err(|_| AppError::Unauthorized(String::from(INVALID_CREDENTIALS)))?;
         Ok(())
     }
+
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
 }
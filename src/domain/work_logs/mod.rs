@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+pub mod request;
+pub mod response;
+pub mod review;
+pub mod service;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogBody<T> {
+    pub work_log: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogsBody<T> {
+    pub work_logs: Vec<T>,
+    pub work_logs_count: i64,
+}
@@ -0,0 +1,60 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::Deserialize;
+
+use crate::prisma::WorkLogStatus;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogCreateInput {
+    pub employer_id: i32,
+    pub job_type: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: Option<DateTime<FixedOffset>>,
+    pub break_minutes: Option<i32>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogUpdateInput {
+    pub employer_id: Option<i32>,
+    pub job_type: Option<String>,
+    pub start_time: Option<DateTime<FixedOffset>>,
+    pub end_time: Option<DateTime<FixedOffset>>,
+    pub break_minutes: Option<i32>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogListQuery {
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+    pub status: Option<WorkLogStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogPendingQuery {
+    pub employer_id: Option<i32>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogRejectInput {
+    /// Sent to the worker so they know what to amend.
+    pub reason: String,
+}
+
+/// Approves every pending log of an employer's crew that started on `date`
+/// (UTC).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogApproveDayInput {
+    pub employer_id: i32,
+    pub date: NaiveDate,
+}
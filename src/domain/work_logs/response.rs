@@ -0,0 +1,56 @@
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::prisma::{work_log, WorkLogStatus};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLog {
+    pub id: i32,
+    pub worker_id: i32,
+    pub employer_id: i32,
+    pub job_type: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: Option<DateTime<FixedOffset>>,
+    pub break_minutes: i32,
+    pub worked_minutes: Option<i64>,
+    pub notes: Option<String>,
+    pub status: WorkLogStatus,
+    pub reviewed_by_id: Option<i32>,
+    pub reviewed_at: Option<DateTime<FixedOffset>>,
+    pub rejection_reason: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl work_log::Data {
+    /// Minutes actually worked, i.e. the shift length minus the break.
+    /// `None` while the shift is still open.
+    pub fn worked_minutes(&self) -> Option<i64> {
+        self.end_time.map(|end_time| {
+            ((end_time - self.start_time).num_minutes() - self.break_minutes as i64).max(0)
+        })
+    }
+}
+
+impl From<work_log::Data> for WorkLog {
+    fn from(data: work_log::Data) -> Self {
+        Self {
+            worked_minutes: data.worked_minutes(),
+            id: data.id,
+            worker_id: data.worker_id,
+            employer_id: data.employer_id,
+            job_type: data.job_type,
+            start_time: data.start_time,
+            end_time: data.end_time,
+            break_minutes: data.break_minutes,
+            notes: data.notes,
+            status: data.status,
+            reviewed_by_id: data.reviewed_by_id,
+            reviewed_at: data.reviewed_at,
+            rejection_reason: data.rejection_reason,
+            created_at: data.created_at,
+            updated_at: data.updated_at,
+        }
+    }
+}
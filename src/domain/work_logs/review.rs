@@ -0,0 +1,33 @@
+use crate::prisma::WorkLogStatus;
+
+/// Statuses waiting for a supervisor.
+pub const PENDING: [WorkLogStatus; 2] = [WorkLogStatus::Submitted, WorkLogStatus::Amended];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+/// The status a supervisor's decision moves a log to. Only submitted and
+/// amended logs can be reviewed; a rejected log goes back to the queue once
+/// the worker amends it.
+pub fn review(status: WorkLogStatus, decision: Decision) -> Result<WorkLogStatus, &'static str> {
+    match status {
+        WorkLogStatus::Submitted | WorkLogStatus::Amended => Ok(match decision {
+            Decision::Approve => WorkLogStatus::Approved,
+            Decision::Reject => WorkLogStatus::Rejected,
+        }),
+        WorkLogStatus::Approved => Err("Work log is already approved"),
+        WorkLogStatus::Rejected => Err("Work log must be amended before it is reviewed again"),
+    }
+}
+
+/// The status after the worker edits a log. Approved logs are final.
+pub fn amend(status: WorkLogStatus) -> Result<WorkLogStatus, &'static str> {
+    match status {
+        WorkLogStatus::Submitted => Ok(WorkLogStatus::Submitted),
+        WorkLogStatus::Rejected | WorkLogStatus::Amended => Ok(WorkLogStatus::Amended),
+        WorkLogStatus::Approved => Err("Approved work logs can no longer be changed"),
+    }
+}
@@ -0,0 +1,556 @@
+use axum::{extract::State, http::StatusCode, Extension};
+use prisma_client_rust::{
+    chrono::{self, DateTime, Duration, FixedOffset, NaiveTime, TimeZone},
+    Direction,
+};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::{
+    app_error::AppError,
+    config::AppContext,
+    domain::attachments::service::AttachmentsService,
+    extractor::{roles, AuthUser, Json, Path, Query, RequireRole},
+    mailer,
+    pagination::clamp_limit,
+    prisma::{
+        attachment, employer, invoice, invoice_line, user,
+        work_log::{self, WhereParam},
+        InvoiceStatus, PrismaClient, WorkLogStatus,
+    },
+    telemetry::Observe,
+    tenant::TenantClient,
+    validation::{check_field, rules},
+};
+
+use super::{
+    request::{
+        WorkLogApproveDayInput, WorkLogCreateInput, WorkLogListQuery, WorkLogPendingQuery,
+        WorkLogRejectInput, WorkLogUpdateInput,
+    },
+    response::WorkLog,
+    review::{self, Decision},
+    WorkLogBody, WorkLogsBody,
+};
+
+type Prisma = Extension<Arc<PrismaClient>>;
+
+pub struct WorkLogsService;
+
+impl WorkLogsService {
+    pub async fn create_work_log(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        prisma: Prisma,
+        Json(input): Json<WorkLogBody<WorkLogCreateInput>>,
+    ) -> Result<Json<WorkLogBody<WorkLog>>, AppError> {
+        let WorkLogBody {
+            work_log:
+                WorkLogCreateInput {
+                    employer_id,
+                    job_type,
+                    start_time,
+                    end_time,
+                    break_minutes,
+                    notes,
+                },
+        } = input;
+
+        let break_minutes = break_minutes.unwrap_or(0);
+        Self::validate_times(&start_time, &end_time, break_minutes)?;
+
+        let employer = tenant.employers().find_by_id(employer_id).await?;
+
+        let data = prisma
+            .work_log()
+            .create(
+                tenant.organization(),
+                user::id::equals(auth_user.user_id),
+                employer::id::equals(employer.id),
+                job_type,
+                start_time,
+                vec![
+                    work_log::end_time::set(end_time),
+                    work_log::break_minutes::set(break_minutes),
+                    work_log::notes::set(notes),
+                ],
+            )
+            .observed()
+            .exec()
+            .await?;
+
+        Ok(Json::from(WorkLogBody {
+            work_log: data.into(),
+        }))
+    }
+
+    pub async fn list_work_logs(
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        Query(query): Query<WorkLogListQuery>,
+    ) -> Result<Json<WorkLogsBody<WorkLog>>, AppError> {
+        let work_logs_count = tenant
+            .work_logs()
+            .count(Self::list_filters(auth_user.user_id, &query))
+            .exec()
+            .await?;
+
+        let work_logs = tenant
+            .work_logs()
+            .find_many(Self::list_filters(auth_user.user_id, &query))
+            .order_by(work_log::start_time::order(Direction::Desc))
+            .skip(query.offset.unwrap_or(0).max(0))
+            .take(clamp_limit(query.limit))
+            .exec()
+            .await?
+            .into_iter()
+            .map(WorkLog::from)
+            .collect();
+
+        Ok(Json::from(WorkLogsBody {
+            work_logs,
+            work_logs_count,
+        }))
+    }
+
+    pub async fn get_work_log(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+    ) -> Result<Json<WorkLogBody<WorkLog>>, AppError> {
+        let data = Self::find_own(&tenant, id, auth_user.user_id).await?;
+
+        Ok(Json::from(WorkLogBody {
+            work_log: data.into(),
+        }))
+    }
+
+    pub async fn update_work_log(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        Json(input): Json<WorkLogBody<WorkLogUpdateInput>>,
+    ) -> Result<Json<WorkLogBody<WorkLog>>, AppError> {
+        let WorkLogBody {
+            work_log:
+                WorkLogUpdateInput {
+                    employer_id,
+                    job_type,
+                    start_time,
+                    end_time,
+                    break_minutes,
+                    notes,
+                },
+        } = input;
+
+        let data = Self::find_own(&tenant, id, auth_user.user_id).await?;
+        Self::ensure_unlocked(&tenant, &data).await?;
+
+        Self::validate_times(
+            &start_time.unwrap_or(data.start_time),
+            &end_time.or(data.end_time),
+            break_minutes.unwrap_or(data.break_minutes),
+        )?;
+
+        let status = review::amend(data.status).map_err(Self::conflict)?;
+        let mut params = vec![work_log::status::set(status)];
+        if let Some(employer_id) = employer_id {
+            let employer = tenant.employers().find_by_id(employer_id).await?;
+            params.push(work_log::employer_id::set(employer.id));
+        }
+        if let Some(job_type) = job_type {
+            params.push(work_log::job_type::set(job_type));
+        }
+        if let Some(start_time) = start_time {
+            params.push(work_log::start_time::set(start_time));
+        }
+        if let Some(end_time) = end_time {
+            params.push(work_log::end_time::set(Some(end_time)));
+        }
+        if let Some(break_minutes) = break_minutes {
+            params.push(work_log::break_minutes::set(break_minutes));
+        }
+        if let Some(notes) = notes {
+            params.push(work_log::notes::set(Some(notes)));
+        }
+
+        // A supervisor may have reviewed the log since it was read
+        let updated = tenant
+            .work_logs()
+            .update_many(
+                vec![
+                    work_log::id::equals(data.id),
+                    work_log::status::equals(data.status),
+                ],
+                params,
+            )
+            .exec()
+            .await?;
+        if updated == 0 {
+            return Err(Self::reviewed_meanwhile());
+        }
+
+        let data = tenant.work_logs().find_by_id(data.id).await?;
+
+        Ok(Json::from(WorkLogBody {
+            work_log: data.into(),
+        }))
+    }
+
+    pub async fn delete_work_log(
+        Path(id): Path<i32>,
+        auth_user: AuthUser,
+        tenant: TenantClient,
+        ctx: State<AppContext>,
+    ) -> Result<StatusCode, AppError> {
+        let data = Self::find_own(&tenant, id, auth_user.user_id).await?;
+        Self::ensure_unlocked(&tenant, &data).await?;
+
+        // The records go with the log, their files have to be removed here
+        let attachment_keys: Vec<String> = tenant
+            .attachments()
+            .find_many(vec![attachment::work_log_id::equals(data.id)])
+            .exec()
+            .await?
+            .into_iter()
+            .map(|attachment| attachment.storage_key)
+            .collect();
+
+        let deleted = tenant
+            .work_logs()
+            .delete_many(vec![
+                work_log::id::equals(data.id),
+                work_log::status::equals(data.status),
+            ])
+            .exec()
+            .await?;
+        if deleted == 0 {
+            return Err(Self::reviewed_meanwhile());
+        }
+
+        AttachmentsService::delete_files(ctx.storage.as_ref(), attachment_keys).await;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Pending logs across the organization, oldest first, for supervisors
+    /// to work through.
+    pub async fn list_pending(
+        _supervisor: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+        Query(query): Query<WorkLogPendingQuery>,
+    ) -> Result<Json<WorkLogsBody<WorkLog>>, AppError> {
+        let work_logs_count = tenant
+            .work_logs()
+            .count(Self::pending_filters(query.employer_id))
+            .exec()
+            .await?;
+
+        let work_logs = tenant
+            .work_logs()
+            .find_many(Self::pending_filters(query.employer_id))
+            .order_by(work_log::start_time::order(Direction::Asc))
+            .skip(query.offset.unwrap_or(0).max(0))
+            .take(clamp_limit(query.limit))
+            .exec()
+            .await?
+            .into_iter()
+            .map(WorkLog::from)
+            .collect();
+
+        Ok(Json::from(WorkLogsBody {
+            work_logs,
+            work_logs_count,
+        }))
+    }
+
+    pub async fn approve_work_log(
+        Path(id): Path<i32>,
+        supervisor: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+    ) -> Result<Json<WorkLogBody<WorkLog>>, AppError> {
+        let data = Self::review(&tenant, id, supervisor.user_id, Decision::Approve, None).await?;
+
+        Ok(Json::from(WorkLogBody {
+            work_log: data.into(),
+        }))
+    }
+
+    /// Rejects the log and emails the reason to the worker, if they have a
+    /// verified address. A failed email does not undo the rejection.
+    pub async fn reject_work_log(
+        Path(id): Path<i32>,
+        supervisor: RequireRole<roles::Supervisor>,
+        ctx: State<AppContext>,
+        tenant: TenantClient,
+        Json(input): Json<WorkLogBody<WorkLogRejectInput>>,
+    ) -> Result<Json<WorkLogBody<WorkLog>>, AppError> {
+        let WorkLogBody {
+            work_log: WorkLogRejectInput { reason },
+        } = input;
+
+        check_field("reason", rules::not_empty(&reason))?;
+
+        let data = Self::review(
+            &tenant,
+            id,
+            supervisor.user_id,
+            Decision::Reject,
+            Some(reason.clone()),
+        )
+        .await?;
+
+        let worker = tenant.users().find_by_id(data.worker_id).await?;
+        if let (Some(email), true) = (worker.email, worker.verified) {
+            let ctx = ctx.0.clone();
+            let body = Self::rejection_email_body(&data, &reason);
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = mailer::send_text(&ctx, &email, "Your work log was rejected", body)
+                {
+                    error!("Failed to send work log rejection email: {:?}", e);
+                }
+            });
+        }
+
+        Ok(Json::from(WorkLogBody {
+            work_log: data.into(),
+        }))
+    }
+
+    /// Approves every pending, closed log at the employer that started on the
+    /// given day, except the supervisor's own.
+    pub async fn approve_day(
+        supervisor: RequireRole<roles::Supervisor>,
+        tenant: TenantClient,
+        Json(input): Json<WorkLogBody<WorkLogApproveDayInput>>,
+    ) -> Result<Json<WorkLogsBody<WorkLog>>, AppError> {
+        let WorkLogBody {
+            work_log: WorkLogApproveDayInput { employer_id, date },
+        } = input;
+
+        let employer = tenant.employers().find_by_id(employer_id).await?;
+        let day_start: DateTime<FixedOffset> = chrono::Utc
+            .from_utc_datetime(&date.and_time(NaiveTime::MIN))
+            .into();
+
+        let pending_ids: Vec<i32> = tenant
+            .work_logs()
+            .find_many(vec![
+                work_log::employer_id::equals(employer.id),
+                work_log::start_time::gte(day_start),
+                work_log::start_time::lt(day_start + Duration::days(1)),
+                work_log::status::in_vec(review::PENDING.to_vec()),
+                work_log::end_time::not(None),
+                work_log::worker_id::not(supervisor.user_id),
+            ])
+            .exec()
+            .await?
+            .into_iter()
+            .map(|log| log.id)
+            .collect();
+
+        // Logs amended or reviewed since the read above are left alone
+        tenant
+            .work_logs()
+            .update_many(
+                vec![
+                    work_log::id::in_vec(pending_ids.clone()),
+                    work_log::status::in_vec(review::PENDING.to_vec()),
+                ],
+                vec![
+                    work_log::status::set(WorkLogStatus::Approved),
+                    work_log::reviewed_by_id::set(Some(supervisor.user_id)),
+                    work_log::reviewed_at::set(Some(Self::now())),
+                    work_log::rejection_reason::set(None),
+                ],
+            )
+            .exec()
+            .await?;
+
+        let work_logs: Vec<WorkLog> = tenant
+            .work_logs()
+            .find_many(vec![
+                work_log::id::in_vec(pending_ids),
+                work_log::status::equals(WorkLogStatus::Approved),
+                work_log::reviewed_by_id::equals(Some(supervisor.user_id)),
+            ])
+            .order_by(work_log::start_time::order(Direction::Asc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(WorkLog::from)
+            .collect();
+
+        Ok(Json::from(WorkLogsBody {
+            work_logs_count: work_logs.len() as i64,
+            work_logs,
+        }))
+    }
+
+    async fn review(
+        tenant: &TenantClient,
+        id: i32,
+        reviewer_id: i32,
+        decision: Decision,
+        rejection_reason: Option<String>,
+    ) -> Result<work_log::Data, AppError> {
+        let data = tenant.work_logs().find_by_id(id).await?;
+
+        if data.worker_id == reviewer_id {
+            return Err(AppError::Forbidden(String::from(
+                "You cannot review your own work logs",
+            )));
+        }
+        if data.end_time.is_none() {
+            return Err(AppError::Conflict(String::from(
+                "Open shifts cannot be reviewed",
+            )));
+        }
+        let status = review::review(data.status, decision).map_err(Self::conflict)?;
+
+        let updated = tenant
+            .work_logs()
+            .update_many(
+                vec![
+                    work_log::id::equals(data.id),
+                    work_log::status::equals(data.status),
+                ],
+                vec![
+                    work_log::status::set(status),
+                    work_log::reviewed_by_id::set(Some(reviewer_id)),
+                    work_log::reviewed_at::set(Some(Self::now())),
+                    work_log::rejection_reason::set(rejection_reason),
+                ],
+            )
+            .exec()
+            .await?;
+        if updated == 0 {
+            return Err(Self::reviewed_meanwhile());
+        }
+
+        tenant.work_logs().find_by_id(data.id).await
+    }
+
+    async fn find_own(
+        tenant: &TenantClient,
+        id: i32,
+        worker_id: i32,
+    ) -> Result<work_log::Data, AppError> {
+        tenant
+            .work_logs()
+            .find_first(vec![
+                work_log::id::equals(id),
+                work_log::worker_id::equals(worker_id),
+            ])
+            .exec()
+            .await?
+            .ok_or(AppError::NotFound(String::from("Work log not found")))
+    }
+
+    /// Approved logs, and logs paid by a finalized payroll run or billed on
+    /// an issued invoice, are frozen.
+    async fn ensure_unlocked(tenant: &TenantClient, data: &work_log::Data) -> Result<(), AppError> {
+        if data.payroll_run_id.is_some() {
+            return Err(AppError::Conflict(String::from(
+                "Work log belongs to a finalized payroll run",
+            )));
+        }
+
+        let invoiced = tenant
+            .invoices()
+            .count(vec![
+                invoice::status::in_vec(vec![InvoiceStatus::Issued, InvoiceStatus::Paid]),
+                invoice::lines::some(vec![invoice_line::work_log_id::equals(Some(data.id))]),
+            ])
+            .exec()
+            .await?;
+        if invoiced > 0 {
+            return Err(AppError::Conflict(String::from(
+                "Work log is billed on an issued invoice",
+            )));
+        }
+
+        if data.status == WorkLogStatus::Approved {
+            return Err(AppError::Conflict(String::from(
+                "Approved work logs can no longer be changed",
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn list_filters(worker_id: i32, query: &WorkLogListQuery) -> Vec<WhereParam> {
+        let mut filters = vec![work_log::worker_id::equals(worker_id)];
+        if let Some(from) = query.from {
+            filters.push(work_log::start_time::gte(from));
+        }
+        if let Some(to) = query.to {
+            filters.push(work_log::start_time::lt(to));
+        }
+        if let Some(status) = query.status {
+            filters.push(work_log::status::equals(status));
+        }
+        filters
+    }
+
+    /// Closed logs waiting for review.
+    fn pending_filters(employer_id: Option<i32>) -> Vec<WhereParam> {
+        let mut filters = vec![
+            work_log::status::in_vec(review::PENDING.to_vec()),
+            work_log::end_time::not(None),
+        ];
+        if let Some(employer_id) = employer_id {
+            filters.push(work_log::employer_id::equals(employer_id));
+        }
+        filters
+    }
+
+    pub(crate) fn validate_times(
+        start_time: &DateTime<FixedOffset>,
+        end_time: &Option<DateTime<FixedOffset>>,
+        break_minutes: i32,
+    ) -> Result<(), AppError> {
+        if break_minutes < 0 {
+            return Err(AppError::BadRequest(String::from(
+                "Break minutes must not be negative",
+            )));
+        }
+
+        if let Some(end_time) = end_time {
+            if end_time <= start_time {
+                return Err(AppError::BadRequest(String::from(
+                    "End time must be after start time",
+                )));
+            }
+            rules::break_fits_shift(start_time, end_time, break_minutes)
+                .map_err(|message| AppError::BadRequest(String::from(message)))?;
+        }
+
+        Ok(())
+    }
+
+    fn rejection_email_body(data: &work_log::Data, reason: &str) -> String {
+        format!(
+            "Your {} shift on {} was rejected by a supervisor.\n\n\
+             Reason: {}\n\n\
+             Please amend the work log so it can be reviewed again.",
+            data.job_type,
+            data.start_time.naive_utc().date(),
+            reason
+        )
+    }
+
+    fn conflict(message: &str) -> AppError {
+        AppError::Conflict(String::from(message))
+    }
+
+    fn reviewed_meanwhile() -> AppError {
+        AppError::Conflict(String::from(
+            "Work log was changed in the meantime, please reload",
+        ))
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
+}
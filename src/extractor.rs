@@ -1,28 +1,112 @@
 use axum::{
     async_trait,
-    extract::{FromRef, FromRequestParts},
-    http::{header::AUTHORIZATION, request::Parts, HeaderValue},
+    extract::{FromRef, FromRequest, FromRequestParts, Request},
+    http::{header::AUTHORIZATION, request::Parts, HeaderName, HeaderValue},
+    response::{IntoResponse, Response},
 };
 use jsonwebtoken::{encode, TokenData};
 use prisma_client_rust::chrono;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{marker::PhantomData, sync::Arc};
 
 use tracing::{debug, info};
 
-use crate::{app_error::AppError, config::AppContext};
+use crate::{
+    app_error::AppError,
+    config::AppContext,
+    domain::sessions::service::SessionsService,
+    prisma::{user, PrismaClient, Role},
+    telemetry::Observe,
+};
 
 const AUTH_HEADER_PREFIX: &str = "Token ";
 
-#[derive(Debug)]
+pub const ORG_ID_HEADER: HeaderName = HeaderName::from_static("x-org-id");
+
+/// The authenticated user. When an organization is selected, `role` is the
+/// user's membership role in it, otherwise the platform-wide `User.role`.
+#[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: i32,
+    pub role: Role,
+    pub session_id: i32,
+    pub org_id: Option<i32>,
 }
 
+/// The organization requested through the `X-Org-Id` header, used to pick
+/// the organization a new token is issued for.
+#[derive(Debug)]
+pub struct OrgSelection(pub Option<i32>);
+
 #[derive(Debug)]
 pub struct OptionalAuthUser(pub Option<AuthUser>);
 
+/// An [`AuthUser`] who has verified their email address through OTP.
+/// Rejects with [`AppError::Forbidden`] otherwise.
+#[derive(Debug)]
+pub struct VerifiedAuthUser(pub AuthUser);
+
+/// An [`AuthUser`] whose role satisfies `R`, e.g. `RequireRole<roles::Supervisor>`.
+/// Rejects with [`AppError::Forbidden`] otherwise.
+#[derive(Debug)]
+pub struct RequireRole<R: RoleRequirement>(pub AuthUser, PhantomData<R>);
+
+pub trait RoleRequirement {
+    const ALLOWED: &'static [Role];
+}
+
+pub mod roles {
+    use super::RoleRequirement;
+    use crate::prisma::Role;
+
+    #[derive(Debug)]
+    pub struct Worker;
+
+    #[derive(Debug)]
+    pub struct Supervisor;
+
+    #[derive(Debug)]
+    pub struct Employer;
+
+    #[derive(Debug)]
+    pub struct Admin;
+
+    impl RoleRequirement for Worker {
+        const ALLOWED: &'static [Role] = &[Role::Worker, Role::Supervisor, Role::Admin];
+    }
+
+    impl RoleRequirement for Supervisor {
+        const ALLOWED: &'static [Role] = &[Role::Supervisor, Role::Admin];
+    }
+
+    impl RoleRequirement for Employer {
+        const ALLOWED: &'static [Role] = &[Role::Employer, Role::Admin];
+    }
+
+    impl RoleRequirement for Admin {
+        const ALLOWED: &'static [Role] = &[Role::Admin];
+    }
+}
+
+/// `axum::Json` that rejects malformed bodies with the error envelope
+/// instead of plain text. Also used for JSON responses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+/// `axum::extract::Query` that rejects with the error envelope.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query<T>(pub T);
+
+/// `axum::extract::Path` that rejects with the error envelope.
+#[derive(Debug)]
+pub struct Path<T>(pub T);
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct AuthUserClaims {
     user_id: i32,
+    role: Role,
+    sid: i32,
+    org_id: Option<i32>,
     exp: i64,
 }
 
@@ -31,13 +115,20 @@ impl AuthUser {
         let key = jsonwebtoken::EncodingKey::from_secret(ctx.config.jwt.secret.as_ref());
         let claims = AuthUserClaims {
             user_id: self.user_id,
-            exp: chrono::Utc::now().timestamp() + ctx.config.jwt.exp_in_sec,
+            role: self.role,
+            sid: self.session_id,
+            org_id: self.org_id,
+            exp: chrono::Utc::now().timestamp() + ctx.config.jwt.exp.as_secs() as i64,
         };
 
         encode(&jsonwebtoken::Header::default(), &claims, &key).unwrap()
     }
 
-    fn from_authorization(ctx: &AppContext, auth_header: &HeaderValue) -> Result<Self, AppError> {
+    async fn from_authorization(
+        ctx: &AppContext,
+        prisma: &PrismaClient,
+        auth_header: &HeaderValue,
+    ) -> Result<Self, AppError> {
         let auth_header = auth_header.to_str().map_err(|_| {
             info!("Authorization header is not UTF-8");
             AppError::Unauthorized(String::from("Authorization header is not UTF-8"))
@@ -79,10 +170,64 @@ impl AuthUser {
             return Err(AppError::Unauthorized(String::from("JWT is expired")));
         }
 
+        if !SessionsService::is_active(prisma, claims.sid, claims.user_id).await? {
+            debug!("Session {} is revoked or expired", claims.sid);
+            return Err(AppError::Unauthorized(String::from(
+                "Session has been revoked",
+            )));
+        }
+
         Ok(Self {
             user_id: claims.user_id,
+            role: claims.role,
+            session_id: claims.sid,
+            org_id: claims.org_id,
         })
     }
+
+    pub fn has_role(&self, roles: &[Role]) -> bool {
+        roles.contains(&self.role)
+    }
+}
+
+impl VerifiedAuthUser {
+    /// For handlers that only need a verified address for some changes and
+    /// have already loaded the caller's record.
+    pub fn check(auth_user: AuthUser, verified: bool) -> Result<Self, AppError> {
+        if !verified {
+            return Err(AppError::Forbidden(String::from(
+                "Email address has not been verified",
+            )));
+        }
+
+        Ok(Self(auth_user))
+    }
+}
+
+impl std::ops::Deref for VerifiedAuthUser {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<R: RoleRequirement> std::ops::Deref for RequireRole<R> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+pub(crate) fn prisma_from_parts(parts: &Parts) -> Result<Arc<PrismaClient>, AppError> {
+    parts
+        .extensions
+        .get::<Arc<PrismaClient>>()
+        .cloned()
+        .ok_or(AppError::Anyhow(anyhow::anyhow!(
+            "Prisma client extension is missing"
+        )))
 }
 
 impl From<OptionalAuthUser> for Option<AuthUser> {
@@ -100,7 +245,13 @@ where
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Several extractors of one handler may need the user, only check once
+        if let Some(auth_user) = parts.extensions.get::<AuthUser>() {
+            return Ok(auth_user.clone());
+        }
+
         let ctx: AppContext = AppContext::from_ref(state);
+        let prisma = prisma_from_parts(parts)?;
 
         let auth_header = parts
             .headers
@@ -109,7 +260,10 @@ where
                 "Missing Authorization header",
             )))?;
 
-        Self::from_authorization(&ctx, auth_header)
+        let auth_user = Self::from_authorization(&ctx, &prisma, auth_header).await?;
+        parts.extensions.insert(auth_user.clone());
+
+        Ok(auth_user)
     }
 }
 
@@ -123,13 +277,150 @@ where
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let ctx: AppContext = AppContext::from_ref(state);
+        let prisma = prisma_from_parts(parts)?;
+
+        let auth_user = match parts.headers.get(AUTHORIZATION) {
+            Some(auth_header) => AuthUser::from_authorization(&ctx, &prisma, auth_header)
+                .await
+                .ok(),
+            None => None,
+        };
+
+        Ok(Self(auth_user))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for VerifiedAuthUser
+where
+    S: Send + Sync,
+    AppContext: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+        let prisma = prisma_from_parts(parts)?;
+
+        let verified = prisma
+            .user()
+            .find_unique(user::id::equals(auth_user.user_id))
+            .observed()
+            .exec()
+            .await?
+            .map(|user| user.verified)
+            .unwrap_or(false);
+
+        Self::check(auth_user, verified)
+    }
+}
+
+#[async_trait]
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    S: Send + Sync,
+    AppContext: FromRef<S>,
+    R: RoleRequirement + Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+        if !auth_user.has_role(R::ALLOWED) {
+            debug!(
+                "User {} with role {:?} is not allowed here",
+                auth_user.user_id, auth_user.role
+            );
+            return Err(AppError::Forbidden(String::from(
+                "You do not have permission to perform this action",
+            )));
+        }
+
+        Ok(Self(auth_user, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for OrgSelection
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let org_id = match parts.headers.get(ORG_ID_HEADER) {
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.trim().parse::<i32>().ok())
+                    .ok_or(AppError::BadRequest(String::from(
+                        "X-Org-Id header must be an organization id",
+                    )))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self(org_id))
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::Json(value) = axum::Json::<T>::from_request(req, state).await?;
+
+        Ok(Self(value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::extract::Query(value) =
+            axum::extract::Query::<T>::from_request_parts(parts, state).await?;
+
+        Ok(Self(value))
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::extract::Path(value) =
+            axum::extract::Path::<T>::from_request_parts(parts, state).await?;
 
-        Ok(Self(
-            parts
-                .headers
-                .get(AUTHORIZATION)
-                .map(|auth_header| AuthUser::from_authorization(&ctx, auth_header).ok())
-                .flatten(),
-        ))
+        Ok(Self(value))
     }
 }
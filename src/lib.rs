@@ -3,5 +3,11 @@ pub mod app_error;
 pub mod config;
 pub mod domain;
 pub mod extractor;
+pub mod mailer;
+pub mod pagination;
 pub mod prisma;
 pub mod router;
+pub mod storage;
+pub mod telemetry;
+pub mod tenant;
+pub mod validation;
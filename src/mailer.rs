@@ -0,0 +1,72 @@
+use lettre::{
+    message::{header::ContentType, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use std::time::Instant;
+
+use crate::{app_error::AppError, config::AppContext, telemetry};
+
+/// Sends an HTML email through the configured SMTP relay.
+///
+/// Sending blocks on the SMTP connection, so callers that do not need the
+/// outcome should run it in `tokio::task::spawn_blocking`.
+pub fn send_html(ctx: &AppContext, to: &str, subject: &str, body: String) -> Result<(), AppError> {
+    send(ctx, to, subject, ContentType::TEXT_HTML, body)
+}
+
+/// Sends a plain text email, for messages that embed user-written text.
+pub fn send_text(ctx: &AppContext, to: &str, subject: &str, body: String) -> Result<(), AppError> {
+    send(ctx, to, subject, ContentType::TEXT_PLAIN, body)
+}
+
+fn send(
+    ctx: &AppContext,
+    to: &str,
+    subject: &str,
+    content_type: ContentType,
+    body: String,
+) -> Result<(), AppError> {
+    let sender = ctx
+        .config
+        .smtp
+        .email
+        .parse()
+        .map_err(|e| anyhow::Error::new(e).context("invalid sender address"))?;
+    let from = Mailbox::new(Some(ctx.config.smtp.name.clone()), sender);
+    let to: Mailbox = format!("<{}>", to)
+        .parse()
+        .map_err(|e| anyhow::Error::new(e).context("invalid recipient address"))?;
+
+    let email = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(subject)
+        .multipart(
+            MultiPart::alternative()
+                .singlepart(SinglePart::builder().header(content_type).body(body)),
+        )
+        .map_err(|e| anyhow::Error::new(e).context("failed to build email"))?;
+
+    let creds = Credentials::new(
+        ctx.config.smtp.email.to_owned(),
+        ctx.config.smtp.password.to_owned(),
+    );
+
+    let mailer = SmtpTransport::starttls_relay(&ctx.config.smtp.server)
+        .map_err(|e| anyhow::Error::new(e).context("failed to connect to SMTP relay"))?
+        .port(ctx.config.smtp.port)
+        .credentials(creds)
+        .build();
+
+    let started = Instant::now();
+    let result = mailer.send(&email);
+    telemetry::record_smtp_send(started, result.is_ok());
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(AppError::Anyhow(
+            anyhow::Error::new(e).context("failed to send email"),
+        )),
+    }
+}
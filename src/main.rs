@@ -1,6 +1,7 @@
 #![warn(clippy::all)]
 
 use std::{
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -11,39 +12,87 @@ use axum::{
     extract::{MatchedPath, Request},
     http::StatusCode,
     middleware::{self, Next},
-    response::IntoResponse,
-    BoxError, Extension, Json,
+    response::{IntoResponse, Response},
+    BoxError, Extension,
 };
+use clap::Parser;
 use emp_logger::{
-    config::{app_config::AppConfig, AppContext},
+    app_error::{error_response, AppError},
+    config::{app_config::AppConfig, storage::StorageDriver, AppContext},
+    domain::{clock::service::ClockService, users::service::UsersService},
     prisma::PrismaClient,
     router::AppRouter,
+    storage, telemetry,
 };
-use serde_json::json;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
+    services::ServeDir,
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Serves the API. Settings come from defaults, a config file, `.env` and
+/// the environment, each overriding the ones before.
+#[derive(Parser)]
+#[command(name = "emp_logger", version)]
+struct Cli {
+    /// Config file to read instead of `CONFIG_FILE` or `config.toml`
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print the effective configuration with secrets redacted, then exit
+    #[arg(long)]
+    print_config: bool,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let config = AppConfig::init();
+    let cli = Cli::parse();
+    let config = AppConfig::load(cli.config.as_deref())?;
+    if cli.print_config {
+        print!("{}", toml::to_string_pretty(&config.redacted())?);
+        return Ok(());
+    }
+
     let app_context = AppContext {
         config: Arc::new(config.clone()),
+        storage: storage::from_config(&config.storage)?,
     };
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(&config.log_level))
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let prisma_client = Arc::new(PrismaClient::_builder().build().await?);
+    let prisma_client = Arc::new(
+        PrismaClient::_builder()
+            .with_url(config.db.url.clone())
+            .build()
+            .await?,
+    );
+    let metrics_handle = telemetry::install_recorder()?;
+
+    tokio::spawn(close_stale_shifts(
+        prisma_client.clone(),
+        app_context.clone(),
+    ));
+    tokio::spawn(release_deleted_accounts(
+        prisma_client.clone(),
+        app_context.clone(),
+    ));
 
     let cors = CorsLayer::new().allow_methods(Any).allow_headers(Any).allow_origin(Any);
 
-    let app = AppRouter::new()
+    // Avatars on local disk are public. Nothing else under the storage root
+    // is served here, other uploads go through handlers that check access.
+    let mut router = AppRouter::new();
+    if let StorageDriver::Local { root } = &config.storage.driver {
+        let avatars = format!("{}/avatars", config.storage.public_url.trim_end_matches('/'));
+        router = router.nest_service(&avatars, ServeDir::new(root.join("avatars")));
+    }
+
+    let app = router
         .layer(cors)
         .layer(Extension(prisma_client))
         .layer(
@@ -52,8 +101,30 @@ async fn main() -> anyhow::Result<()> {
                 .layer(HandleErrorLayer::new(handle_timeout_error))
                 .timeout(Duration::from_secs(30)),
         )
-        .route_layer(middleware::from_fn(track_metrics))
-        .with_state(app_context);
+        .route_layer(middleware::from_fn(track_metrics));
+
+    // A dedicated port keeps scrapes off the public listener. Either way the
+    // router is added after `track_metrics` so scrapes are not counted.
+    let app = match config.metrics.port {
+        Some(metrics_port) => {
+            let metrics_app = telemetry::metrics_router(metrics_handle);
+            let metrics_listener =
+                tokio::net::TcpListener::bind(&format!("0.0.0.0:{}", metrics_port))
+                    .await
+                    .context("error while binding metrics port")?;
+
+            info!("serving metrics on port {}", metrics_port);
+            tokio::spawn(async move {
+                if let Err(err) = axum::serve(metrics_listener, metrics_app).await {
+                    error!("metrics server stopped: {}", err);
+                }
+            });
+
+            app
+        }
+        None => app.merge(telemetry::metrics_router(metrics_handle)),
+    }
+    .with_state(app_context);
 
     info!("starting server on port {}", config.port);
 
@@ -67,31 +138,56 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn handle_timeout_error(err: BoxError) -> (StatusCode, Json<serde_json::Value>) {
+async fn handle_timeout_error(err: BoxError) -> Response {
     if err.is::<tower::timeout::error::Elapsed>() {
-        (
+        error_response(
             StatusCode::REQUEST_TIMEOUT,
-            Json(json!({
-                "error":
-                    format!(
-                        "request took longer than the configured {} second timeout",
-                        30
-                    )
-            })),
+            "REQUEST_TIMEOUT",
+            format!(
+                "request took longer than the configured {} second timeout",
+                30
+            ),
+            None,
         )
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": format!("unhandled internal error: {}", err) })),
-        )
+        AppError::Anyhow(anyhow::anyhow!(err)).into_response()
+    }
+}
+
+async fn close_stale_shifts(prisma: Arc<PrismaClient>, ctx: AppContext) {
+    let mut interval = tokio::time::interval(ctx.config.clock.sweep_interval);
+
+    loop {
+        interval.tick().await;
+
+        match ClockService::close_stale_shifts(&prisma, &ctx).await {
+            Ok(0) => {}
+            Ok(closed) => info!("closed {} forgotten shifts", closed),
+            Err(err) => error!("failed to close forgotten shifts: {:?}", err),
+        }
+    }
+}
+
+async fn release_deleted_accounts(prisma: Arc<PrismaClient>, ctx: AppContext) {
+    let mut interval = tokio::time::interval(ctx.config.accounts.release_interval);
+
+    loop {
+        interval.tick().await;
+
+        match UsersService::release_deleted_accounts(&prisma, &ctx).await {
+            Ok(0) => {}
+            Ok(released) => info!("released {} deleted accounts", released),
+            Err(err) => error!("failed to release deleted accounts: {:?}", err),
+        }
     }
 }
 
 async fn track_metrics(request: Request, next: Next) -> impl IntoResponse {
+    // Label by route template only; raw paths would create a series per id.
     let path = if let Some(matched_path) = request.extensions().get::<MatchedPath>() {
         matched_path.as_str().to_owned()
     } else {
-        request.uri().path().to_owned()
+        String::from("unmatched")
     };
 
     let start = Instant::now();
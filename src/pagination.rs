@@ -0,0 +1,135 @@
+//! Cursor pagination for list endpoints.
+//!
+//! A list is ordered by a sort key plus the row id, which breaks ties. The
+//! cursor handed to clients encodes the key and id of the last row of a
+//! page, and the next page starts strictly after it, so rows inserted while
+//! a client is paging never shift what it sees the way offsets do.
+//!
+//! Lists over time, like reports and histories, are also bounded by a date
+//! range, see [`date_range`].
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use prisma_client_rust::chrono::{self, DateTime, Duration, FixedOffset};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::app_error::AppError;
+
+pub const DEFAULT_LIMIT: i64 = 20;
+pub const MAX_LIMIT: i64 = 100;
+
+/// The number of rows a client asked for, or [`DEFAULT_LIMIT`], clamped to
+/// `1..=MAX_LIMIT`. Also used by lists paged with offsets.
+pub fn clamp_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+/// Resolves an optional `[from, to)` range, defaulting to the last
+/// `default_days` and rejecting ranges longer than `max_days`.
+pub fn date_range(
+    from: Option<DateTime<FixedOffset>>,
+    to: Option<DateTime<FixedOffset>>,
+    default_days: i64,
+    max_days: i64,
+) -> Result<(DateTime<FixedOffset>, DateTime<FixedOffset>), AppError> {
+    let to = to.unwrap_or_else(|| chrono::Utc::now().into());
+    let from = from.unwrap_or(to - Duration::days(default_days));
+
+    if from >= to {
+        return Err(AppError::BadRequest(String::from(
+            "The start of the range must be before its end",
+        )));
+    }
+    if to - from > Duration::days(max_days) {
+        return Err(AppError::BadRequest(format!(
+            "The range may span at most {} days",
+            max_days
+        )));
+    }
+
+    Ok((from, to))
+}
+
+/// Position of a row in a list ordered by `key`, then id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor<K> {
+    pub key: K,
+    pub id: i32,
+}
+
+impl<K: Serialize + DeserializeOwned> Cursor<K> {
+    pub fn new(key: K, id: i32) -> Self {
+        Self { key, id }
+    }
+
+    /// Clients treat the encoded cursor as opaque and pass it back as is.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor keys serialize to JSON");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(value: &str) -> Result<Self, AppError> {
+        URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or(AppError::BadRequest(String::from("Invalid cursor")))
+    }
+}
+
+/// The page a client asked for, with the limit clamped to
+/// `1..=MAX_LIMIT`.
+#[derive(Debug, Clone)]
+pub struct PageRequest<K> {
+    pub limit: i64,
+    pub after: Option<Cursor<K>>,
+}
+
+impl<K: Serialize + DeserializeOwned> PageRequest<K> {
+    pub fn new(limit: Option<i64>, cursor: Option<&str>) -> Result<Self, AppError> {
+        let after = match cursor {
+            Some(cursor) if !cursor.is_empty() => Some(Cursor::decode(cursor)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            limit: clamp_limit(limit),
+            after,
+        })
+    }
+
+    /// Rows to fetch. The one past the limit only tells whether another
+    /// page follows.
+    pub fn take(&self) -> i64 {
+        self.limit + 1
+    }
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from rows fetched with [`PageRequest::take`]. The next
+    /// cursor points at the last row kept, and is only set if there are more.
+    pub fn from_rows<K, F>(mut rows: Vec<T>, request: &PageRequest<K>, cursor: F) -> Self
+    where
+        K: Serialize + DeserializeOwned,
+        F: Fn(&T) -> Cursor<K>,
+    {
+        let has_more = rows.len() as i64 > request.limit;
+        rows.truncate(request.limit as usize);
+
+        let next_cursor = if has_more {
+            rows.last().map(|row| cursor(row).encode())
+        } else {
+            None
+        };
+
+        Self {
+            items: rows,
+            next_cursor,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    config::AppContext,
+    domain::attachments::{files::MAX_ATTACHMENT_BYTES, service::AttachmentsService},
+};
+
+/// Room for the multipart boundaries and the other fields.
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+pub struct AttachmentsRouter;
+
+impl AttachmentsRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/work-logs/:id/attachments",
+                post(AttachmentsService::upload_attachment)
+                    .layer(DefaultBodyLimit::max(
+                        MAX_ATTACHMENT_BYTES + MULTIPART_OVERHEAD_BYTES,
+                    ))
+                    .get(AttachmentsService::list_attachments),
+            )
+            .route(
+                "/attachments/:id",
+                get(AttachmentsService::get_attachment)
+                    .delete(AttachmentsService::delete_attachment),
+            )
+            .route(
+                "/attachments/:id/file",
+                get(AttachmentsService::download_attachment),
+            )
+    }
+}
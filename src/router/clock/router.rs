@@ -0,0 +1,17 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppContext, domain::clock::service::ClockService};
+
+pub struct ClockRouter;
+
+impl ClockRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route("/clock/in", post(ClockService::clock_in))
+            .route("/clock/out", post(ClockService::clock_out))
+            .route("/clock/status", get(ClockService::status))
+    }
+}
@@ -0,0 +1,29 @@
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+
+use crate::{config::AppContext, domain::employers::service::EmployersService};
+
+pub struct EmployersRouter;
+
+impl EmployersRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/employers",
+                post(EmployersService::create_employer).get(EmployersService::list_employers),
+            )
+            .route(
+                "/employers/:id",
+                get(EmployersService::get_employer).put(EmployersService::update_employer),
+            )
+            .route(
+                "/employers/:id/assignments",
+                post(EmployersService::create_assignment)
+                    .get(EmployersService::list_employer_assignments),
+            )
+            .route("/assignments", get(EmployersService::list_my_assignments))
+            .route("/assignments/:id", put(EmployersService::update_assignment))
+    }
+}
@@ -0,0 +1,22 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppContext, domain::harvest::service::HarvestService};
+
+pub struct HarvestRouter;
+
+impl HarvestRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/harvest-tallies",
+                post(HarvestService::create_tally).get(HarvestService::list_tallies),
+            )
+            .route(
+                "/harvest-tallies/daily",
+                get(HarvestService::list_daily_totals),
+            )
+    }
+}
@@ -0,0 +1,13 @@
+use axum::{routing::post, Router};
+
+use crate::{config::AppContext, domain::imports::service::ImportsService};
+
+pub struct ImportsRouter;
+
+impl ImportsRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route("/imports/users", post(ImportsService::import_users))
+            .route("/imports/work-logs", post(ImportsService::import_work_logs))
+    }
+}
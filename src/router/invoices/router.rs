@@ -0,0 +1,26 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppContext, domain::invoices::service::InvoicesService};
+
+pub struct InvoicesRouter;
+
+impl InvoicesRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/invoices",
+                post(InvoicesService::create_invoice).get(InvoicesService::list_invoices),
+            )
+            .route("/invoices/:id", get(InvoicesService::get_invoice))
+            .route("/invoices/:id/issue", post(InvoicesService::issue_invoice))
+            .route("/invoices/:id/pay", post(InvoicesService::pay_invoice))
+            .route("/invoices/:id/void", post(InvoicesService::void_invoice))
+            .route(
+                "/invoices/:id/reconciliation",
+                get(InvoicesService::reconcile_invoice),
+            )
+    }
+}
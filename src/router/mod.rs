@@ -1,6 +1,16 @@
+pub mod attachments;
+pub mod clock;
+pub mod employers;
+pub mod harvest;
+pub mod imports;
+pub mod invoices;
+pub mod organizations;
+pub mod payroll;
 pub mod profiles;
+pub mod reports;
 pub mod users;
 pub mod otp;
+pub mod work_logs;
 
 use axum::{routing::get, Router};
 use users::router::UsersRouter;
@@ -9,7 +19,17 @@ use crate::config::AppContext;
 
 use profiles::router::ProfilesRouter;
 
+use self::attachments::router::AttachmentsRouter;
+use self::clock::router::ClockRouter;
+use self::employers::router::EmployersRouter;
+use self::harvest::router::HarvestRouter;
+use self::imports::router::ImportsRouter;
+use self::invoices::router::InvoicesRouter;
+use self::organizations::router::OrganizationsRouter;
 use self::otp::router::OTPRouter;
+use self::payroll::router::PayrollRouter;
+use self::reports::router::ReportsRouter;
+use self::work_logs::router::WorkLogsRouter;
 
 pub struct AppRouter;
 
@@ -20,6 +40,16 @@ impl AppRouter {
             .nest("/api", UsersRouter::new())
             .nest("/api", ProfilesRouter::new())
             .nest("/api", OTPRouter::new())
+            .nest("/api", WorkLogsRouter::new())
+            .nest("/api", EmployersRouter::new())
+            .nest("/api", OrganizationsRouter::new())
+            .nest("/api", ClockRouter::new())
+            .nest("/api", HarvestRouter::new())
+            .nest("/api", PayrollRouter::new())
+            .nest("/api", InvoicesRouter::new())
+            .nest("/api", ReportsRouter::new())
+            .nest("/api", ImportsRouter::new())
+            .nest("/api", AttachmentsRouter::new())
     }
 }
 
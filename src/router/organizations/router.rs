@@ -0,0 +1,48 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::{config::AppContext, domain::organizations::service::OrganizationsService};
+
+pub struct OrganizationsRouter;
+
+impl OrganizationsRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/organizations",
+                post(OrganizationsService::create_organization)
+                    .get(OrganizationsService::list_organizations),
+            )
+            .route(
+                "/organizations/members",
+                get(OrganizationsService::list_members),
+            )
+            .route(
+                "/organizations/members/:username",
+                delete(OrganizationsService::remove_member),
+            )
+            .route(
+                "/organizations/invitations",
+                post(OrganizationsService::invite_member)
+                    .get(OrganizationsService::list_invitations),
+            )
+            .route(
+                "/organizations/invitations/:id",
+                delete(OrganizationsService::revoke_invitation),
+            )
+            .route(
+                "/user/invitations",
+                get(OrganizationsService::list_received_invitations),
+            )
+            .route(
+                "/user/invitations/:id",
+                delete(OrganizationsService::decline_invitation),
+            )
+            .route(
+                "/user/invitations/:id/accept",
+                post(OrganizationsService::accept_invitation),
+            )
+    }
+}
@@ -1 +1 @@
-pub mod router;
\ No newline at end of file
+pub mod router;
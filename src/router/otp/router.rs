@@ -1,7 +1,4 @@
-use axum::{
-    routing::post,
-    Router,
-};
+use axum::{routing::post, Router};
 
 use crate::{config::AppContext, domain::otp::service::OTPService};
 
@@ -13,4 +10,4 @@ impl OTPRouter {
             .route("/otp/request/:email", post(OTPService::request_otp))
             .route("/otp/verify/:otp", post(OTPService::verify_otp))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,28 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppContext, domain::payroll::service::PayrollService};
+
+pub struct PayrollRouter;
+
+impl PayrollRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/payroll/runs",
+                post(PayrollService::create_run).get(PayrollService::list_runs),
+            )
+            .route("/payroll/runs/preview", post(PayrollService::preview_run))
+            .route("/payroll/runs/:id", get(PayrollService::get_run))
+            .route(
+                "/payroll/runs/:id/finalize",
+                post(PayrollService::finalize_run),
+            )
+            .route(
+                "/payroll/piece-rates",
+                post(PayrollService::upsert_piece_rate).get(PayrollService::list_piece_rates),
+            )
+    }
+}
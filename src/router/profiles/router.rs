@@ -1,5 +1,5 @@
 use axum::{
-    routing::{delete, get, post},
+    routing::{get, post},
     Router,
 };
 
@@ -10,6 +10,23 @@ pub struct ProfilesRouter;
 impl ProfilesRouter {
     pub fn new() -> Router<AppContext> {
         Router::new()
+            .route("/profiles", get(ProfilesService::list_profiles))
             .route("/profiles/:username", get(ProfilesService::get_profile))
+            .route(
+                "/profiles/:username/follow",
+                post(ProfilesService::follow).delete(ProfilesService::unfollow),
+            )
+            .route(
+                "/profiles/:username/followers",
+                get(ProfilesService::list_followers),
+            )
+            .route(
+                "/profiles/:username/following",
+                get(ProfilesService::list_following),
+            )
+            .route(
+                "/profiles/:username/work-history",
+                get(ProfilesService::get_work_history),
+            )
     }
 }
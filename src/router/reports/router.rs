@@ -0,0 +1,19 @@
+use axum::{routing::get, Router};
+
+use crate::{config::AppContext, domain::reports::service::ReportsService};
+
+pub struct ReportsRouter;
+
+impl ReportsRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/reports/work-history.csv",
+                get(ReportsService::work_history_csv),
+            )
+            .route(
+                "/reports/work-history.xlsx",
+                get(ReportsService::work_history_xlsx),
+            )
+    }
+}
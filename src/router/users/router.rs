@@ -1,9 +1,16 @@
 use axum::{
-    routing::{get, post, put},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, post, put},
     Router,
 };
 
-use crate::{config::AppContext, domain::users::service::UsersService};
+use crate::{
+    config::AppContext,
+    domain::users::{avatar::MAX_AVATAR_BYTES, service::UsersService},
+};
+
+/// Room for the multipart boundaries and headers around the image.
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
 
 pub struct UsersRouter;
 
@@ -13,6 +20,26 @@ impl UsersRouter {
             .route("/user", get(UsersService::get_current_user))
             .route("/users", post(UsersService::create_user))
             .route("/users/login", post(UsersService::login))
+            .route("/users/refresh", post(UsersService::refresh))
+            .route("/users/logout", post(UsersService::logout))
+            .route(
+                "/users/password/forgot",
+                post(UsersService::forgot_password),
+            )
+            .route("/users/password/reset", post(UsersService::reset_password))
             .route("/user", put(UsersService::update_user))
+            .route("/user", delete(UsersService::delete_current_user))
+            .route(
+                "/user/avatar",
+                post(UsersService::upload_avatar).layer(DefaultBodyLimit::max(
+                    MAX_AVATAR_BYTES + MULTIPART_OVERHEAD_BYTES,
+                )),
+            )
+            .route("/users/:username/role", put(UsersService::update_role))
+            .route(
+                "/users/:username/deactivate",
+                post(UsersService::deactivate_user),
+            )
+            .route("/users/:username/restore", post(UsersService::restore_user))
     }
 }
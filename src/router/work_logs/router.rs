@@ -0,0 +1,34 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppContext, domain::work_logs::service::WorkLogsService};
+
+pub struct WorkLogsRouter;
+
+impl WorkLogsRouter {
+    pub fn new() -> Router<AppContext> {
+        Router::new()
+            .route(
+                "/work-logs",
+                post(WorkLogsService::create_work_log).get(WorkLogsService::list_work_logs),
+            )
+            .route(
+                "/work-logs/:id",
+                get(WorkLogsService::get_work_log)
+                    .put(WorkLogsService::update_work_log)
+                    .delete(WorkLogsService::delete_work_log),
+            )
+            .route("/work-logs/pending", get(WorkLogsService::list_pending))
+            .route("/work-logs/approve-day", post(WorkLogsService::approve_day))
+            .route(
+                "/work-logs/:id/approve",
+                post(WorkLogsService::approve_work_log),
+            )
+            .route(
+                "/work-logs/:id/reject",
+                post(WorkLogsService::reject_work_log),
+            )
+    }
+}
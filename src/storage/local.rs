@@ -0,0 +1,77 @@
+use axum::{async_trait, body::Bytes};
+use std::{io::ErrorKind, path::PathBuf};
+
+use crate::app_error::AppError;
+
+use super::{invalid_key, is_valid_key, missing_file, Storage};
+
+/// Files on the local disk below `root`. The API serves them itself, see
+/// `main.rs`.
+pub struct LocalStorage {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf, public_url: String) -> Self {
+        Self { root, public_url }
+    }
+
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, AppError> {
+        if !is_valid_key(key) {
+            return Err(invalid_key(key));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, bytes: Bytes) -> Result<(), AppError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| anyhow::Error::new(e).context("failed to create upload directory"))?;
+        }
+
+        // Write next to the target and rename, so nobody is served half a file
+        let partial = path.with_extension("part");
+        tokio::fs::write(&partial, &bytes)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to write upload"))?;
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to move upload in place"))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, AppError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Bytes::from(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(missing_file()),
+            Err(e) => Err(AppError::Anyhow(
+                anyhow::Error::new(e).context("failed to read upload"),
+            )),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Anyhow(
+                anyhow::Error::new(e).context("failed to delete upload"),
+            )),
+        }
+    }
+
+    fn public_url(&self) -> &str {
+        &self.public_url
+    }
+}
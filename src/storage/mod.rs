@@ -0,0 +1,89 @@
+//! Where uploaded files are kept. Handlers only see [`Storage`], so a
+//! deployment picks local disk or an S3-compatible bucket through config.
+
+use axum::{async_trait, body::Bytes};
+use std::sync::Arc;
+
+use crate::{
+    app_error::AppError,
+    config::storage::{StorageConfig, StorageDriver},
+};
+
+pub mod local;
+pub mod s3;
+
+pub use self::{local::LocalStorage, s3::S3Storage};
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores `bytes` under `key`, replacing any file already there.
+    async fn put(&self, key: &str, content_type: &str, bytes: Bytes) -> Result<(), AppError>;
+
+    /// Fails with [`AppError::NotFound`] if nothing is stored under `key`.
+    async fn get(&self, key: &str) -> Result<Bytes, AppError>;
+
+    /// Removing a file that does not exist is not an error.
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+
+    /// Base URL that [`Storage::url`] puts in front of keys.
+    fn public_url(&self) -> &str;
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url().trim_end_matches('/'), key)
+    }
+
+    /// The key of a URL made by [`Storage::url`], or `None` for URLs that
+    /// point elsewhere, e.g. images linked before uploads existed.
+    fn key(&self, url: &str) -> Option<String> {
+        let prefix = format!("{}/", self.public_url().trim_end_matches('/'));
+        url.strip_prefix(&prefix)
+            .filter(|key| is_valid_key(key))
+            .map(String::from)
+    }
+}
+
+pub fn from_config(config: &StorageConfig) -> anyhow::Result<Arc<dyn Storage>> {
+    let storage: Arc<dyn Storage> = match &config.driver {
+        StorageDriver::Local { root } => {
+            Arc::new(LocalStorage::new(root.clone(), config.public_url.clone()))
+        }
+        StorageDriver::S3 {
+            bucket,
+            region,
+            endpoint,
+            access_key,
+            secret_key,
+        } => Arc::new(S3Storage::new(
+            bucket,
+            region,
+            endpoint,
+            access_key,
+            secret_key,
+            config.public_url.clone(),
+        )?),
+    };
+
+    Ok(storage)
+}
+
+/// Keys are relative `/`-separated paths without `.` or `..` segments, so
+/// no driver can be made to write outside its root.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+}
+
+fn missing_file() -> AppError {
+    AppError::NotFound(String::from("File not found"))
+}
+
+fn invalid_key(key: &str) -> AppError {
+    AppError::Anyhow(anyhow::anyhow!("invalid storage key {:?}", key))
+}
@@ -0,0 +1,103 @@
+use axum::{async_trait, body::Bytes};
+use s3::{creds::Credentials, Bucket, Region};
+
+use crate::app_error::AppError;
+
+use super::{invalid_key, is_valid_key, missing_file, Storage};
+
+/// A bucket in any S3-compatible object store. Served straight from the
+/// store or a CDN in front of it, never through the API.
+pub struct S3Storage {
+    bucket: Bucket,
+    public_url: String,
+}
+
+impl S3Storage {
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint: &str,
+        access_key: &str,
+        secret_key: &str,
+        public_url: String,
+    ) -> anyhow::Result<Self> {
+        let region = Region::Custom {
+            region: region.to_string(),
+            endpoint: endpoint.to_string(),
+        };
+        let credentials = Credentials::new(Some(access_key), Some(secret_key), None, None, None)?;
+
+        // Path-style addressing works with MinIO and other stand-ins without
+        // wildcard DNS
+        let bucket = Bucket::new(bucket, region, credentials)?.with_path_style();
+
+        Ok(Self { bucket, public_url })
+    }
+
+    fn check(status: u16, action: &str) -> Result<(), AppError> {
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            Err(AppError::Anyhow(anyhow::anyhow!(
+                "object store answered {} to {}",
+                status,
+                action
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, bytes: Bytes) -> Result<(), AppError> {
+        if !is_valid_key(key) {
+            return Err(invalid_key(key));
+        }
+
+        let response = self
+            .bucket
+            .put_object_with_content_type(key, &bytes, content_type)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to upload object"))?;
+
+        Self::check(response.status_code(), "upload")
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, AppError> {
+        if !is_valid_key(key) {
+            return Err(invalid_key(key));
+        }
+
+        let response = self
+            .bucket
+            .get_object(key)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to download object"))?;
+
+        if response.status_code() == 404 {
+            return Err(missing_file());
+        }
+        Self::check(response.status_code(), "download")?;
+
+        Ok(Bytes::copy_from_slice(response.as_slice()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        if !is_valid_key(key) {
+            return Err(invalid_key(key));
+        }
+
+        let response = self
+            .bucket
+            .delete_object(key)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to delete object"))?;
+
+        // Deleting a missing key succeeds with 204 on S3 and most stand-ins
+        Self::check(response.status_code(), "delete")
+    }
+
+    fn public_url(&self) -> &str {
+        &self.public_url
+    }
+}
@@ -0,0 +1,169 @@
+use axum::{routing::get, Router};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use prisma_client_rust::QueryError;
+use std::{future::Future, time::Instant};
+
+use crate::prisma::{
+    assignment, attachment, clock_entry, employer, follow, harvest_tally, invitation, invoice,
+    invoice_line, membership, organization, otp_code, payroll_line_item, payroll_run, piece_rate,
+    session, user, work_log,
+};
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Installs the Prometheus recorder behind the `metrics` macros.
+pub fn install_recorder() -> anyhow::Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets(LATENCY_BUCKETS)?
+        .install_recorder()?;
+
+    Ok(handle)
+}
+
+/// `GET /metrics` in the Prometheus text format.
+pub fn metrics_router<S>(handle: PrometheusHandle) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route(
+        "/metrics",
+        get(move || {
+            let handle = handle.clone();
+            async move { handle.render() }
+        }),
+    )
+}
+
+/// Runs a database query and records its latency under `query`, which must
+/// be a fixed name such as `"session.is_active"` to keep labels bounded.
+pub async fn observe_query<F: Future>(query: &'static str, future: F) -> F::Output {
+    let start = Instant::now();
+    let output = future.await;
+
+    metrics::histogram!("db_query_duration_seconds", "query" => query)
+        .record(start.elapsed().as_secs_f64());
+
+    output
+}
+
+/// A query builder whose `exec` goes through [`observe_query`]. The tenant
+/// accessors return these, other queries get one from [`Observe::observed`]
+/// right before `exec`.
+pub struct Observed<Q> {
+    query: &'static str,
+    builder: Q,
+}
+
+impl<Q> Observed<Q> {
+    pub fn new(query: &'static str, builder: Q) -> Self {
+        Self { query, builder }
+    }
+
+    /// Changes the wrapped builder, for builder methods not forwarded here.
+    pub fn map(self, f: impl FnOnce(Q) -> Q) -> Self {
+        Self {
+            query: self.query,
+            builder: f(self.builder),
+        }
+    }
+}
+
+/// Query builders that can be measured, labelled with their model and
+/// action, e.g. `user.find_unique`.
+pub trait Observe: Sized {
+    const QUERY: &'static str;
+
+    fn observed(self) -> Observed<Self> {
+        Observed::new(Self::QUERY, self)
+    }
+}
+
+macro_rules! observed_models {
+    ($($model:ident),* $(,)?) => {
+        $(
+            observed_action!($model, FindUnique, find_unique, Option<$model::Data>);
+            observed_action!($model, FindFirst, find_first, Option<$model::Data>);
+            observed_action!($model, FindMany, find_many, Vec<$model::Data>);
+            observed_action!($model, Count, count, i64);
+            observed_action!($model, Create, create, $model::Data);
+            observed_action!($model, Update, update, $model::Data);
+            observed_action!($model, Upsert, upsert, $model::Data);
+            observed_action!($model, Delete, delete, $model::Data);
+            observed_action!($model, UpdateMany, update_many, i64);
+            observed_action!($model, DeleteMany, delete_many, i64);
+
+            impl<'a> Observed<$model::FindMany<'a>> {
+                pub fn with(self, param: impl Into<$model::WithParam>) -> Self {
+                    self.map(|builder| builder.with(param))
+                }
+
+                pub fn order_by(self, param: $model::OrderByParam) -> Self {
+                    self.map(|builder| builder.order_by(param))
+                }
+
+                pub fn take(self, value: i64) -> Self {
+                    self.map(|builder| builder.take(value))
+                }
+
+                pub fn skip(self, value: i64) -> Self {
+                    self.map(|builder| builder.skip(value))
+                }
+            }
+
+            impl<'a> Observed<$model::FindFirst<'a>> {
+                pub fn with(self, param: impl Into<$model::WithParam>) -> Self {
+                    self.map(|builder| builder.with(param))
+                }
+
+                pub fn order_by(self, param: $model::OrderByParam) -> Self {
+                    self.map(|builder| builder.order_by(param))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! observed_action {
+    ($model:ident, $builder:ident, $action:ident, $output:ty) => {
+        impl<'a> Observe for $model::$builder<'a> {
+            const QUERY: &'static str = concat!(stringify!($model), ".", stringify!($action));
+        }
+
+        impl<'a> Observed<$model::$builder<'a>> {
+            pub async fn exec(self) -> Result<$output, QueryError> {
+                observe_query(self.query, self.builder.exec()).await
+            }
+        }
+    };
+}
+
+observed_models!(
+    assignment,
+    attachment,
+    clock_entry,
+    employer,
+    follow,
+    harvest_tally,
+    invitation,
+    invoice,
+    invoice_line,
+    membership,
+    organization,
+    otp_code,
+    payroll_line_item,
+    payroll_run,
+    piece_rate,
+    session,
+    user,
+    work_log,
+);
+
+pub fn record_smtp_send(started: Instant, success: bool) {
+    let status = if success { "success" } else { "failure" };
+
+    metrics::counter!("smtp_emails_total", "status" => status).increment(1);
+    metrics::histogram!("smtp_send_duration_seconds", "status" => status)
+        .record(started.elapsed().as_secs_f64());
+}
@@ -0,0 +1,320 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use prisma_client_rust::operator::and;
+use std::sync::Arc;
+
+use crate::{
+    app_error::AppError,
+    config::AppContext,
+    extractor::{prisma_from_parts, AuthUser},
+    prisma::{
+        assignment, attachment, clock_entry, employer, harvest_tally, invitation, invoice,
+        membership, organization, payroll_run, piece_rate, user, work_log, PrismaClient,
+    },
+    telemetry::{self, Observe, Observed},
+};
+
+/// Database access restricted to the organization of the authenticated user.
+///
+/// Every read, update and delete issued through the scoped accessors carries
+/// the organization filter, so a handler cannot reach another tenant's rows
+/// by forgetting a `where` clause. Creates still go through `PrismaClient`,
+/// but every tenant-owned model requires an organization relation on create,
+/// which handlers take from [`TenantClient::organization`].
+pub struct TenantClient {
+    prisma: Arc<PrismaClient>,
+    org_id: i32,
+}
+
+macro_rules! scoped_model {
+    ($scoped:ident, $model:ident, $not_found:literal, $scope:expr) => {
+        pub struct $scoped<'a> {
+            client: &'a PrismaClient,
+            org_id: i32,
+        }
+
+        impl<'a> $scoped<'a> {
+            fn scope(&self, mut params: Vec<$model::WhereParam>) -> Vec<$model::WhereParam> {
+                let scope: fn(i32) -> $model::WhereParam = $scope;
+                params.push(scope(self.org_id));
+                params
+            }
+
+            pub fn find_many(
+                self,
+                params: Vec<$model::WhereParam>,
+            ) -> Observed<$model::FindMany<'a>> {
+                let params = self.scope(params);
+                self.client.$model().find_many(params).observed()
+            }
+
+            pub fn find_first(
+                self,
+                params: Vec<$model::WhereParam>,
+            ) -> Observed<$model::FindFirst<'a>> {
+                let params = self.scope(params);
+                self.client.$model().find_first(params).observed()
+            }
+
+            pub fn count(self, params: Vec<$model::WhereParam>) -> Observed<$model::Count<'a>> {
+                let params = self.scope(params);
+                self.client.$model().count(params).observed()
+            }
+
+            pub fn update_many(
+                self,
+                params: Vec<$model::WhereParam>,
+                data: Vec<$model::SetParam>,
+            ) -> Observed<$model::UpdateMany<'a>> {
+                let params = self.scope(params);
+                self.client.$model().update_many(params, data).observed()
+            }
+
+            pub fn delete_many(
+                self,
+                params: Vec<$model::WhereParam>,
+            ) -> Observed<$model::DeleteMany<'a>> {
+                let params = self.scope(params);
+                self.client.$model().delete_many(params).observed()
+            }
+
+            pub async fn find_by_id(&self, id: i32) -> Result<$model::Data, AppError> {
+                let query = self
+                    .client
+                    .$model()
+                    .find_first(self.scope(vec![$model::id::equals(id)]))
+                    .exec();
+
+                telemetry::observe_query(concat!(stringify!($model), ".find_by_id"), query)
+                    .await?
+                    .ok_or(AppError::NotFound(String::from($not_found)))
+            }
+
+            pub async fn update_by_id(
+                &self,
+                id: i32,
+                data: Vec<$model::SetParam>,
+            ) -> Result<$model::Data, AppError> {
+                self.find_by_id(id).await?;
+
+                let query = self
+                    .client
+                    .$model()
+                    .update($model::id::equals(id), data)
+                    .exec();
+
+                Ok(
+                    telemetry::observe_query(concat!(stringify!($model), ".update_by_id"), query)
+                        .await?,
+                )
+            }
+
+            pub async fn delete_by_id(&self, id: i32) -> Result<$model::Data, AppError> {
+                self.find_by_id(id).await?;
+
+                let query = self.client.$model().delete($model::id::equals(id)).exec();
+
+                Ok(
+                    telemetry::observe_query(concat!(stringify!($model), ".delete_by_id"), query)
+                        .await?,
+                )
+            }
+        }
+    };
+}
+
+// Soft-deleted users are hidden from every tenant query
+scoped_model!(ScopedUsers, user, "User not found", |org_id| {
+    and(vec![
+        user::memberships::some(vec![membership::organization_id::equals(org_id)]),
+        user::deleted_at::equals(None),
+    ])
+});
+scoped_model!(
+    ScopedMemberships,
+    membership,
+    "Membership not found",
+    membership::organization_id::equals
+);
+scoped_model!(
+    ScopedInvitations,
+    invitation,
+    "Invitation not found",
+    invitation::organization_id::equals
+);
+scoped_model!(
+    ScopedEmployers,
+    employer,
+    "Employer not found",
+    employer::organization_id::equals
+);
+scoped_model!(
+    ScopedAssignments,
+    assignment,
+    "Assignment not found",
+    assignment::organization_id::equals
+);
+scoped_model!(
+    ScopedWorkLogs,
+    work_log,
+    "Work log not found",
+    work_log::organization_id::equals
+);
+scoped_model!(
+    ScopedClockEntries,
+    clock_entry,
+    "Clock entry not found",
+    clock_entry::organization_id::equals
+);
+scoped_model!(
+    ScopedHarvestTallies,
+    harvest_tally,
+    "Harvest tally not found",
+    harvest_tally::organization_id::equals
+);
+scoped_model!(
+    ScopedPieceRates,
+    piece_rate,
+    "Piece rate not found",
+    piece_rate::organization_id::equals
+);
+scoped_model!(
+    ScopedPayrollRuns,
+    payroll_run,
+    "Payroll run not found",
+    payroll_run::organization_id::equals
+);
+scoped_model!(
+    ScopedInvoices,
+    invoice,
+    "Invoice not found",
+    invoice::organization_id::equals
+);
+scoped_model!(
+    ScopedAttachments,
+    attachment,
+    "Attachment not found",
+    attachment::organization_id::equals
+);
+
+impl TenantClient {
+    pub fn new(prisma: Arc<PrismaClient>, org_id: i32) -> Self {
+        Self { prisma, org_id }
+    }
+
+    pub fn org_id(&self) -> i32 {
+        self.org_id
+    }
+
+    /// Relation argument for creating records owned by this organization.
+    pub fn organization(&self) -> organization::UniqueWhereParam {
+        organization::id::equals(self.org_id)
+    }
+
+    pub fn users(&self) -> ScopedUsers<'_> {
+        ScopedUsers {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn memberships(&self) -> ScopedMemberships<'_> {
+        ScopedMemberships {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn invitations(&self) -> ScopedInvitations<'_> {
+        ScopedInvitations {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn employers(&self) -> ScopedEmployers<'_> {
+        ScopedEmployers {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn assignments(&self) -> ScopedAssignments<'_> {
+        ScopedAssignments {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn work_logs(&self) -> ScopedWorkLogs<'_> {
+        ScopedWorkLogs {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn clock_entries(&self) -> ScopedClockEntries<'_> {
+        ScopedClockEntries {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn harvest_tallies(&self) -> ScopedHarvestTallies<'_> {
+        ScopedHarvestTallies {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn piece_rates(&self) -> ScopedPieceRates<'_> {
+        ScopedPieceRates {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn payroll_runs(&self) -> ScopedPayrollRuns<'_> {
+        ScopedPayrollRuns {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn invoices(&self) -> ScopedInvoices<'_> {
+        ScopedInvoices {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+
+    pub fn attachments(&self) -> ScopedAttachments<'_> {
+        ScopedAttachments {
+            client: &self.prisma,
+            org_id: self.org_id,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for TenantClient
+where
+    S: Send + Sync,
+    AppContext: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+        let prisma = prisma_from_parts(parts)?;
+
+        let org_id = auth_user.org_id.ok_or(AppError::Forbidden(String::from(
+            "No organization selected",
+        )))?;
+
+        Ok(Self::new(prisma, org_id))
+    }
+}
@@ -0,0 +1,159 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    Json,
+};
+use serde::de::DeserializeOwned;
+
+use crate::app_error::{AppError, FieldError};
+
+/// Input that can check itself before reaching a handler. Implementations
+/// record every problem instead of stopping at the first one, so clients get
+/// all field errors in a single response.
+pub trait Validate {
+    fn validate(&self, errors: &mut ValidationErrors);
+
+    fn check(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::default();
+        self.validate(&mut errors);
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &str, message: &str) {
+        self.0.push(FieldError::new(field, message));
+    }
+
+    /// Records the outcome of one of the [`rules`].
+    pub fn check(&mut self, field: &str, result: Result<(), &'static str>) {
+        if let Err(message) = result {
+            self.add(field, message);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(self.0))
+        }
+    }
+}
+
+/// Validates a single value outside of a request body, e.g. a path segment.
+pub fn check_field(field: &str, result: Result<(), &'static str>) -> Result<(), AppError> {
+    let mut errors = ValidationErrors::default();
+    errors.check(field, result);
+    errors.into_result()
+}
+
+/// `Json<T>` that also runs [`Validate`], rejecting with a 422 listing every
+/// invalid field.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+
+        value.check()?;
+
+        Ok(Self(value))
+    }
+}
+
+pub mod rules {
+    use lazy_static::lazy_static;
+    use prisma_client_rust::chrono::{DateTime, FixedOffset};
+    use regex::Regex;
+
+    pub const USERNAME_MIN_LEN: usize = 3;
+    pub const USERNAME_MAX_LEN: usize = 32;
+    pub const PASSWORD_MIN_LEN: usize = 8;
+    pub const PASSWORD_MAX_LEN: usize = 128;
+    pub const BIO_MAX_LEN: usize = 500;
+    pub const URL_MAX_LEN: usize = 2048;
+
+    lazy_static! {
+        static ref USERNAME_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9_.-]+$").unwrap();
+        static ref EMAIL_REGEX: Regex =
+            Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
+        static ref IMAGE_URL_REGEX: Regex = Regex::new(r"^https?://[^\s/?#]+[^\s]*$").unwrap();
+    }
+
+    pub fn username(value: &str) -> Result<(), &'static str> {
+        let len = value.chars().count();
+        if !(USERNAME_MIN_LEN..=USERNAME_MAX_LEN).contains(&len) {
+            return Err("Username must be between 3 and 32 characters");
+        }
+        if !USERNAME_REGEX.is_match(value) {
+            return Err("Username may only contain letters, digits, '.', '_' and '-'");
+        }
+        Ok(())
+    }
+
+    pub fn password(value: &str) -> Result<(), &'static str> {
+        let len = value.chars().count();
+        if !(PASSWORD_MIN_LEN..=PASSWORD_MAX_LEN).contains(&len) {
+            return Err("Password must be between 8 and 128 characters");
+        }
+        if !value.chars().any(|c| c.is_alphabetic()) || !value.chars().any(|c| c.is_numeric()) {
+            return Err("Password must contain at least one letter and one digit");
+        }
+        Ok(())
+    }
+
+    pub fn email(value: &str) -> Result<(), &'static str> {
+        if !EMAIL_REGEX.is_match(value) {
+            return Err("Invalid email format");
+        }
+        Ok(())
+    }
+
+    pub fn image_url(value: &str) -> Result<(), &'static str> {
+        if value.len() > URL_MAX_LEN || !IMAGE_URL_REGEX.is_match(value) {
+            return Err("Image must be an http(s) URL");
+        }
+        Ok(())
+    }
+
+    pub fn bio(value: &str) -> Result<(), &'static str> {
+        if value.chars().count() > BIO_MAX_LEN {
+            return Err("Bio must be at most 500 characters");
+        }
+        Ok(())
+    }
+
+    pub fn not_empty(value: &str) -> Result<(), &'static str> {
+        if value.trim().is_empty() {
+            return Err("Must not be empty");
+        }
+        Ok(())
+    }
+
+    /// Expects `end_time` to be after `start_time`.
+    pub fn break_fits_shift(
+        start_time: &DateTime<FixedOffset>,
+        end_time: &DateTime<FixedOffset>,
+        break_minutes: i32,
+    ) -> Result<(), &'static str> {
+        if (*end_time - *start_time).num_minutes() < break_minutes as i64 {
+            return Err("Break is longer than the shift");
+        }
+        Ok(())
+    }
+}
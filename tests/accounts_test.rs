@@ -0,0 +1,17 @@
+use emp_logger::{domain::users::service::UsersService, validation::rules};
+
+#[test]
+fn released_usernames_cannot_be_registered() {
+    let username = UsersService::released_username(42);
+
+    assert_eq!(username, "deleted#42");
+    assert!(rules::username(&username).is_err());
+}
+
+#[test]
+fn released_usernames_are_unique_per_user() {
+    assert_ne!(
+        UsersService::released_username(1),
+        UsersService::released_username(11)
+    );
+}
@@ -0,0 +1,51 @@
+use emp_logger::domain::attachments::files::{check, file_name, sniff, MAX_ATTACHMENT_BYTES};
+
+#[test]
+fn recognises_photos_and_documents() {
+    let types = [
+        (&b"\xFF\xD8\xFF\xE0\x00\x10JFIF"[..], "image/jpeg"),
+        (&b"\x89PNG\r\n\x1a\n\x00\x00"[..], "image/png"),
+        (&b"RIFF\x24\x00\x00\x00WEBPVP8 "[..], "image/webp"),
+        (&b"\x00\x00\x00\x18ftypheic\x00\x00"[..], "image/heic"),
+        (&b"%PDF-1.7\n"[..], "application/pdf"),
+    ];
+
+    for (bytes, content_type) in types {
+        assert_eq!(sniff(bytes).unwrap().content_type, content_type);
+    }
+}
+
+#[test]
+fn ignores_what_the_file_claims_to_be() {
+    assert_eq!(
+        check(b"<html><script>alert(1)</script></html>").unwrap_err(),
+        "File must be a JPEG, PNG, WebP or HEIC image, or a PDF"
+    );
+    assert_eq!(check(b"").unwrap_err(), "Must not be empty");
+}
+
+#[test]
+fn rejects_oversized_files() {
+    let mut bytes = b"%PDF-1.7\n".to_vec();
+    bytes.resize(MAX_ATTACHMENT_BYTES + 1, b' ');
+
+    assert_eq!(check(&bytes).unwrap_err(), "File must be at most 10 MB");
+}
+
+#[test]
+fn cleans_up_file_names() {
+    let pdf = sniff(b"%PDF-1.7\n").unwrap();
+    let jpeg = sniff(b"\xFF\xD8\xFF\xE0").unwrap();
+
+    assert_eq!(
+        file_name(Some("Timesheet week 14.pdf"), pdf),
+        "Timesheet week 14.pdf"
+    );
+    assert_eq!(
+        file_name(Some("C:\\Users\\jo\\row 4.JPG"), jpeg),
+        "row 4.jpg"
+    );
+    assert_eq!(file_name(Some("../../\"evil\".html"), pdf), "_evil_.pdf");
+    assert_eq!(file_name(Some(".."), pdf), "attachment.pdf");
+    assert_eq!(file_name(None, jpeg), "attachment.jpg");
+}
@@ -0,0 +1,54 @@
+use emp_logger::domain::users::avatar::{thumbnails, AVATAR_SIZES, MAX_AVATAR_BYTES};
+use image::{ImageFormat, RgbImage};
+use std::io::Cursor;
+
+fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+    let image = RgbImage::from_fn(width, height, |x, y| image::Rgb([x as u8, y as u8, 128]));
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), format)
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn renders_square_png_thumbnails() {
+    let thumbnails = thumbnails(&encode(640, 480, ImageFormat::Jpeg)).unwrap();
+
+    assert_eq!(thumbnails.len(), AVATAR_SIZES.len());
+    for (thumbnail, size) in thumbnails.iter().zip(AVATAR_SIZES) {
+        let decoded =
+            image::load_from_memory_with_format(&thumbnail.png, ImageFormat::Png).unwrap();
+        assert_eq!(thumbnail.size, size);
+        assert_eq!((decoded.width(), decoded.height()), (size, size));
+    }
+}
+
+#[test]
+fn sniffs_the_format_from_the_content() {
+    assert_eq!(
+        thumbnails(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>").unwrap_err(),
+        "Avatar must be a PNG, JPEG or WebP image"
+    );
+}
+
+#[test]
+fn rejects_truncated_images() {
+    let png = encode(64, 64, ImageFormat::Png);
+
+    assert_eq!(
+        thumbnails(&png[..png.len() / 2]).unwrap_err(),
+        "Avatar could not be read as an image"
+    );
+}
+
+#[test]
+fn rejects_oversized_uploads() {
+    let mut bytes = encode(8, 8, ImageFormat::Png);
+    bytes.resize(MAX_AVATAR_BYTES + 1, 0);
+
+    assert_eq!(
+        thumbnails(&bytes).unwrap_err(),
+        "Avatar must be at most 5 MB"
+    );
+}
@@ -0,0 +1,77 @@
+use axum::{
+    body::Body,
+    extract::{FromRequest, FromRequestParts},
+    http::{header::CONTENT_TYPE, Request},
+};
+use emp_logger::{
+    app_error::AppError,
+    extractor::{Json, Query},
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Input {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    limit: u32,
+}
+
+fn json_request(content_type: Option<&str>, body: &'static str) -> Request<Body> {
+    let mut builder = Request::builder().method("POST").uri("/");
+    if let Some(content_type) = content_type {
+        builder = builder.header(CONTENT_TYPE, content_type);
+    }
+    builder.body(Body::from(body)).unwrap()
+}
+
+#[tokio::test]
+async fn reads_json_bodies() {
+    let request = json_request(Some("application/json"), r#"{"name":"Jane"}"#);
+
+    let Json(input) = Json::<Input>::from_request(request, &()).await.unwrap();
+
+    assert_eq!(input.name, "Jane");
+}
+
+#[tokio::test]
+async fn rejects_bad_json_with_the_error_envelope() {
+    let requests = [
+        json_request(Some("application/json"), r#"{"name":"#),
+        json_request(Some("application/json"), r#"{"name":1}"#),
+        json_request(None, r#"{"name":"Jane"}"#),
+    ];
+
+    for request in requests {
+        let error = Json::<Input>::from_request(request, &()).await.unwrap_err();
+
+        assert!(matches!(error, AppError::BadRequest(_)), "{:?}", error);
+        assert_eq!(error.code(), "BAD_REQUEST");
+    }
+}
+
+#[tokio::test]
+async fn rejects_bad_query_strings_with_the_error_envelope() {
+    let (mut parts, _) = Request::builder()
+        .uri("/?limit=20")
+        .body(())
+        .unwrap()
+        .into_parts();
+    let Query(page) = Query::<Page>::from_request_parts(&mut parts, &())
+        .await
+        .unwrap();
+    assert_eq!(page.limit, 20);
+
+    let (mut parts, _) = Request::builder()
+        .uri("/?limit=many")
+        .body(())
+        .unwrap()
+        .into_parts();
+    let error = Query::<Page>::from_request_parts(&mut parts, &())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, AppError::BadRequest(_)), "{:?}", error);
+}
@@ -0,0 +1,95 @@
+use emp_logger::{
+    domain::harvest::totals::{daily_totals, Tally},
+    prisma::HarvestUnit::{self, Bin, Crate, Kilogram},
+};
+use prisma_client_rust::chrono::{DateTime, FixedOffset, NaiveDate};
+
+fn tally(worker_id: i32, crop: &str, unit: HarvestUnit, quantity: f64, at: &str) -> Tally {
+    Tally {
+        worker_id,
+        username: None,
+        crop: String::from(crop),
+        unit,
+        quantity,
+        recorded_at: DateTime::<FixedOffset>::parse_from_rfc3339(at).unwrap(),
+    }
+}
+
+fn date(date: &str) -> NaiveDate {
+    date.parse().unwrap()
+}
+
+#[test]
+fn never_sums_different_units_of_a_crop() {
+    let tallies = vec![
+        tally(1, "apples", Bin, 2.0, "2024-04-01T08:00:00Z"),
+        tally(1, "apples", Kilogram, 30.0, "2024-04-01T09:00:00Z"),
+        tally(1, "apples", Bin, 3.0, "2024-04-01T10:00:00Z"),
+    ];
+
+    let totals = daily_totals(tallies);
+
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0].unit, Bin);
+    assert_eq!(totals[0].quantity, 5.0);
+    assert_eq!(totals[0].tallies_count, 2);
+    assert_eq!(totals[1].unit, Kilogram);
+    assert_eq!(totals[1].quantity, 30.0);
+    assert_eq!(totals[1].tallies_count, 1);
+}
+
+#[test]
+fn splits_days_at_utc_midnight() {
+    let tallies = vec![
+        // 22:30 UTC on the 1st
+        tally(1, "apples", Bin, 1.0, "2024-04-02T00:30:00+02:00"),
+        tally(1, "apples", Bin, 2.0, "2024-04-01T23:59:59Z"),
+        tally(1, "apples", Bin, 4.0, "2024-04-02T00:00:00Z"),
+        // 01:30 UTC on the 2nd
+        tally(1, "apples", Bin, 8.0, "2024-04-01T23:30:00-02:00"),
+    ];
+
+    let totals = daily_totals(tallies);
+
+    let days: Vec<_> = totals
+        .iter()
+        .map(|total| (total.date, total.quantity))
+        .collect();
+    assert_eq!(
+        days,
+        vec![(date("2024-04-01"), 3.0), (date("2024-04-02"), 12.0)]
+    );
+}
+
+#[test]
+fn orders_by_day_worker_crop_and_unit() {
+    let tallies = vec![
+        tally(2, "apples", Bin, 1.0, "2024-04-01T08:00:00Z"),
+        tally(1, "pears", Crate, 1.0, "2024-04-01T08:00:00Z"),
+        tally(1, "pears", Bin, 1.0, "2024-04-01T08:00:00Z"),
+        tally(1, "apples", Kilogram, 1.0, "2024-04-02T08:00:00Z"),
+        tally(1, "apples", Kilogram, 1.0, "2024-04-01T08:00:00Z"),
+    ];
+    let expected = [
+        (date("2024-04-01"), 1, "apples", Kilogram),
+        (date("2024-04-01"), 1, "pears", Bin),
+        (date("2024-04-01"), 1, "pears", Crate),
+        (date("2024-04-01"), 2, "apples", Bin),
+        (date("2024-04-02"), 1, "apples", Kilogram),
+    ];
+
+    let mut reversed = tallies.clone();
+    reversed.reverse();
+    for tallies in [tallies, reversed] {
+        let order: Vec<_> = daily_totals(tallies)
+            .iter()
+            .map(|total| (total.date, total.worker_id, total.crop.clone(), total.unit))
+            .collect();
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(date, worker_id, crop, unit)| (*date, *worker_id, crop.to_string(), *unit))
+            .collect();
+
+        assert_eq!(order, expected);
+    }
+}
@@ -0,0 +1,91 @@
+use emp_logger::domain::imports::parser::{parse_users, parse_work_logs, RowError};
+
+#[test]
+fn parses_valid_users() {
+    let parsed = parse_users(
+        b"username,first_name,last_name,email\n\
+          jdoe,John,Doe,jdoe@example.com\n\
+          asmith,,,\n",
+    );
+
+    assert!(parsed.errors.is_empty());
+    assert_eq!(parsed.total, 2);
+    assert_eq!(parsed.rows.len(), 2);
+    assert_eq!(parsed.rows[0].row, 2);
+    assert_eq!(parsed.rows[0].email.as_deref(), Some("jdoe@example.com"));
+    assert_eq!(parsed.rows[1].first_name, None);
+}
+
+#[test]
+fn reports_duplicates_within_the_file() {
+    let parsed = parse_users(
+        b"username,email\n\
+          jdoe,jdoe@example.com\n\
+          JDoe,other@example.com\n\
+          asmith,JDOE@example.com\n",
+    );
+
+    assert_eq!(parsed.rows.len(), 1);
+    assert_eq!(
+        parsed.errors,
+        vec![
+            RowError::new(3, "username", "Duplicate username in file"),
+            RowError::new(4, "email", "Duplicate email in file"),
+        ]
+    );
+}
+
+#[test]
+fn rejects_files_missing_required_columns() {
+    let parsed =
+        parse_work_logs(b"username,employer,start_time\njdoe,Orchard,2024-04-01T06:00:00Z\n");
+
+    assert!(parsed.rows.is_empty());
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].row, 1);
+    assert_eq!(
+        parsed.errors[0].message,
+        "Missing columns: job_type, end_time"
+    );
+}
+
+#[test]
+fn parses_valid_work_logs() {
+    let parsed = parse_work_logs(
+        b"username,employer,job_type,start_time,end_time,break_minutes,notes\n\
+          jdoe,North Orchard,Picking,2024-04-01T06:00:00Z,2024-04-01T14:30:00Z,30,Rows 1-4\n",
+    );
+
+    assert!(parsed.errors.is_empty());
+    let row = &parsed.rows[0];
+    assert_eq!(row.break_minutes, 30);
+    assert_eq!((row.end_time - row.start_time).num_minutes(), 510);
+    assert_eq!(row.notes.as_deref(), Some("Rows 1-4"));
+}
+
+#[test]
+fn reports_every_problem_in_a_work_log_row() {
+    let parsed = parse_work_logs(
+        b"username,employer,job_type,start_time,end_time,break_minutes\n\
+          jdoe,,Picking,yesterday,2024-04-01T14:30:00Z,-5\n\
+          jdoe,Orchard,Picking,2024-04-01T14:30:00Z,2024-04-01T06:00:00Z,\n\
+          jdoe,Orchard,Picking,2024-04-01T06:00:00Z,2024-04-01T07:00:00Z,61\n",
+    );
+
+    assert!(parsed.rows.is_empty());
+    let fields: Vec<(usize, &str)> = parsed
+        .errors
+        .iter()
+        .map(|error| (error.row, error.field.as_str()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            (2, "employer"),
+            (2, "start_time"),
+            (2, "break_minutes"),
+            (3, "end_time"),
+            (4, "break_minutes"),
+        ]
+    );
+}
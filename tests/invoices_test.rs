@@ -0,0 +1,54 @@
+use emp_logger::domain::invoices::billing::{totals, BillableLine};
+
+#[test]
+fn bills_worked_hours_at_the_hourly_rate() {
+    let line = BillableLine::hours(1, String::from("picking"), 450, 32.0);
+
+    assert_eq!(line.work_log_id, Some(1));
+    assert_eq!(line.harvest_tally_id, None);
+    assert_eq!(line.quantity, 7.5);
+    assert_eq!(line.amount, 240.0);
+}
+
+#[test]
+fn rounds_each_line_to_cents() {
+    // 20 minutes at 25.00 is 8.333...
+    let line = BillableLine::hours(1, String::from("pruning"), 20, 25.0);
+
+    assert_eq!(line.amount, 8.33);
+}
+
+#[test]
+fn bills_pieces_at_the_billing_rate() {
+    let line = BillableLine::pieces(7, String::from("apples"), 12.5, 3.1);
+
+    assert_eq!(line.harvest_tally_id, Some(7));
+    assert_eq!(line.amount, 38.75);
+}
+
+#[test]
+fn applies_tax_once_on_the_subtotal() {
+    let totals = totals(vec![8.33, 8.33, 8.33], 0.15);
+
+    assert_eq!(totals.subtotal, 24.99);
+    assert_eq!(totals.tax, 3.75);
+    assert_eq!(totals.total, 28.74);
+}
+
+#[test]
+fn recomputing_stored_lines_gives_the_same_totals() {
+    let lines = vec![
+        BillableLine::hours(1, String::new(), 437, 31.5),
+        BillableLine::pieces(2, String::new(), 3.0, 47.25),
+    ];
+    let stored = totals(lines.iter().map(|line| line.amount), 0.1);
+
+    let recomputed = totals(
+        lines.iter().map(|line| {
+            BillableLine::pieces(0, String::new(), line.quantity, line.unit_price).amount
+        }),
+        0.1,
+    );
+
+    assert_eq!(stored, recomputed);
+}
@@ -0,0 +1,63 @@
+use emp_logger::pagination::{
+    clamp_limit, date_range, Cursor, Page, PageRequest, DEFAULT_LIMIT, MAX_LIMIT,
+};
+use prisma_client_rust::chrono::{DateTime, Duration};
+
+#[test]
+fn cursors_round_trip() {
+    let cursor = Cursor::new(String::from("jdoe"), 42);
+
+    let decoded = Cursor::<String>::decode(&cursor.encode()).unwrap();
+
+    assert_eq!(decoded, cursor);
+}
+
+#[test]
+fn rejects_tampered_cursors() {
+    assert!(Cursor::<String>::decode("not a cursor").is_err());
+    assert!(Cursor::<i32>::decode(&Cursor::new(String::from("jdoe"), 1).encode()).is_err());
+}
+
+#[test]
+fn clamps_the_limit() {
+    let default = PageRequest::<String>::new(None, None).unwrap();
+    let too_large = PageRequest::<String>::new(Some(10_000), None).unwrap();
+    let too_small = PageRequest::<String>::new(Some(0), Some("")).unwrap();
+
+    assert_eq!(default.limit, DEFAULT_LIMIT);
+    assert_eq!(too_large.limit, MAX_LIMIT);
+    assert_eq!(too_small.limit, 1);
+    assert!(too_small.after.is_none());
+
+    assert_eq!(clamp_limit(Some(50)), 50);
+    assert_eq!(clamp_limit(Some(-5)), 1);
+}
+
+#[test]
+fn sets_the_next_cursor_only_when_more_rows_follow() {
+    let request = PageRequest::<i32>::new(Some(2), None).unwrap();
+    let cursor = |row: &(i32, i32)| Cursor::new(row.1, row.0);
+
+    let page = Page::from_rows(vec![(1, 10), (2, 20), (3, 30)], &request, cursor);
+    assert_eq!(page.items, vec![(1, 10), (2, 20)]);
+    assert_eq!(
+        Cursor::<i32>::decode(page.next_cursor.as_deref().unwrap()).unwrap(),
+        Cursor::new(20, 2)
+    );
+
+    let last = Page::from_rows(vec![(3, 30)], &request, cursor);
+    assert_eq!(last.items, vec![(3, 30)]);
+    assert!(last.next_cursor.is_none());
+}
+
+#[test]
+fn bounds_date_ranges() {
+    let to = DateTime::parse_from_rfc3339("2024-04-30T00:00:00Z").unwrap();
+
+    let (from, until) = date_range(None, Some(to), 7, 31).unwrap();
+    assert_eq!((from, until), (to - Duration::days(7), to));
+
+    assert!(date_range(Some(to), Some(to), 7, 31).is_err());
+    assert!(date_range(Some(to - Duration::days(32)), Some(to), 7, 31).is_err());
+    assert!(date_range(Some(to - Duration::days(31)), Some(to), 7, 31).is_ok());
+}
@@ -0,0 +1,79 @@
+use emp_logger::domain::payroll::calculator::{calculate, PayPolicy, PieceWork, Shift};
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+
+fn policy(deduction_rate: f64) -> PayPolicy {
+    PayPolicy {
+        overtime_threshold_minutes: 480,
+        overtime_multiplier: 1.5,
+        deduction_rate,
+    }
+}
+
+fn shift(worker_id: i32, start: &str, worked_minutes: i64, hourly_rate: f64) -> Shift {
+    let start = DateTime::<FixedOffset>::parse_from_rfc3339(start).unwrap();
+    Shift {
+        worker_id,
+        date: start.naive_utc().date(),
+        start,
+        worked_minutes,
+        hourly_rate,
+    }
+}
+
+#[test]
+fn pays_overtime_past_the_daily_threshold() {
+    let shifts = vec![
+        shift(1, "2024-04-01T06:00:00Z", 360, 20.0),
+        shift(1, "2024-04-01T13:00:00Z", 240, 30.0),
+    ];
+
+    let lines = calculate(&policy(0.0), &shifts, &[]);
+
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].regular_minutes, 480);
+    assert_eq!(lines[0].overtime_minutes, 120);
+    // 6h at 20 plus 2h at 30, then 2h at 30 * 1.5
+    assert_eq!(lines[0].hourly_pay, 180.0);
+    assert_eq!(lines[0].overtime_pay, 90.0);
+    assert_eq!(lines[0].gross_pay, 270.0);
+}
+
+#[test]
+fn threshold_resets_each_day() {
+    let shifts = vec![
+        shift(1, "2024-04-01T06:00:00Z", 480, 20.0),
+        shift(1, "2024-04-02T06:00:00Z", 480, 20.0),
+    ];
+
+    let lines = calculate(&policy(0.0), &shifts, &[]);
+
+    assert_eq!(lines[0].overtime_minutes, 0);
+    assert_eq!(lines[0].hourly_pay, 320.0);
+}
+
+#[test]
+fn adds_piece_pay_and_deductions_per_worker() {
+    let shifts = vec![shift(2, "2024-04-01T06:00:00Z", 60, 15.0)];
+    let pieces = vec![
+        PieceWork {
+            worker_id: 1,
+            quantity: 12.0,
+            rate: 2.5,
+        },
+        PieceWork {
+            worker_id: 2,
+            quantity: 3.0,
+            rate: 1.0,
+        },
+    ];
+
+    let lines = calculate(&policy(0.1), &shifts, &pieces);
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].worker_id, 1);
+    assert_eq!(lines[0].piece_pay, 30.0);
+    assert_eq!(lines[0].deductions, 3.0);
+    assert_eq!(lines[0].net_pay, 27.0);
+    assert_eq!(lines[1].gross_pay, 18.0);
+    assert_eq!(lines[1].net_pay, 16.2);
+}
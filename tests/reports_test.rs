@@ -0,0 +1,57 @@
+use emp_logger::domain::reports::export::{csv_record, escape_formula, ReportTotals, COLUMNS};
+
+#[test]
+fn writes_the_header_row() {
+    let record = csv_record(COLUMNS).unwrap();
+
+    assert_eq!(
+        String::from_utf8(record).unwrap(),
+        "Date,Worker,Employer,Job type,Start,End,Break minutes,Worked hours,Status\n"
+    );
+}
+
+#[test]
+fn quotes_fields_with_separators() {
+    let record = csv_record(["Orchard \"North\"", "Pruning, rows 1-4", "plain"]).unwrap();
+
+    assert_eq!(
+        String::from_utf8(record).unwrap(),
+        "\"Orchard \"\"North\"\"\",\"Pruning, rows 1-4\",plain\n"
+    );
+}
+
+#[test]
+fn escapes_cells_that_start_a_formula() {
+    let cells = [
+        "=HYPERLINK(\"http://evil.test\")",
+        "+1",
+        "-2+3",
+        "@SUM(A1)",
+        "Pruning = fun",
+    ]
+    .map(escape_formula);
+
+    let record = csv_record(&cells).unwrap();
+
+    assert_eq!(
+        String::from_utf8(record).unwrap(),
+        "\"'=HYPERLINK(\"\"http://evil.test\"\")\",'+1,'-2+3,'@SUM(A1),Pruning = fun\n"
+    );
+}
+
+#[test]
+fn totals_row_lines_up_with_the_columns() {
+    let totals = ReportTotals {
+        entries: 3,
+        break_minutes: 90,
+        worked_minutes: 1_330,
+    };
+
+    let cells = totals.cells();
+
+    assert_eq!(cells.len(), COLUMNS.len());
+    assert_eq!(cells[0], "Total");
+    assert_eq!(cells[6], "90");
+    assert_eq!(cells[7], "22.17");
+    assert_eq!(cells[8], "3 entries");
+}
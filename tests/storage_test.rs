@@ -0,0 +1,89 @@
+use axum::body::Bytes;
+use emp_logger::storage::{is_valid_key, LocalStorage, S3Storage, Storage};
+
+#[test]
+fn accepts_only_relative_keys() {
+    assert!(is_valid_key("avatars/1/00ff/256.png"));
+
+    assert!(!is_valid_key(""));
+    assert!(!is_valid_key("/etc/passwd"));
+    assert!(!is_valid_key("avatars/../../etc/passwd"));
+    assert!(!is_valid_key("avatars//256.png"));
+    assert!(!is_valid_key("avatars/a b.png"));
+}
+
+#[test]
+fn maps_keys_to_urls_and_back() {
+    let storage = LocalStorage::new(
+        "uploads".into(),
+        String::from("https://api.example.com/uploads/"),
+    );
+
+    let url = storage.url("avatars/1/00ff/256.png");
+
+    assert_eq!(
+        url,
+        "https://api.example.com/uploads/avatars/1/00ff/256.png"
+    );
+    assert_eq!(storage.key(&url).as_deref(), Some("avatars/1/00ff/256.png"));
+    assert_eq!(storage.key("https://gravatar.com/avatar/abc"), None);
+    assert_eq!(
+        storage.key("https://api.example.com/uploads/../secret"),
+        None
+    );
+}
+
+#[tokio::test]
+async fn stores_and_deletes_files_on_disk() {
+    let root = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(root.path().to_path_buf(), String::from("/uploads"));
+
+    storage
+        .put(
+            "avatars/1/00ff/64.png",
+            "image/png",
+            Bytes::from_static(b"png"),
+        )
+        .await
+        .unwrap();
+    let path = root.path().join("avatars/1/00ff/64.png");
+    assert_eq!(std::fs::read(&path).unwrap(), b"png");
+
+    storage.delete("avatars/1/00ff/64.png").await.unwrap();
+    assert!(!path.exists());
+
+    // Deleting twice is fine
+    storage.delete("avatars/1/00ff/64.png").await.unwrap();
+    assert!(storage
+        .put("../escape", "text/plain", Bytes::new())
+        .await
+        .is_err());
+}
+
+/// Runs against a local MinIO, e.g.
+/// `docker-compose --profile s3 up minio`, then
+/// `S3_TEST_ENDPOINT=http://localhost:9000 cargo test -- --ignored`.
+#[tokio::test]
+#[ignore]
+async fn stores_and_deletes_objects_in_s3() {
+    let endpoint = std::env::var("S3_TEST_ENDPOINT").unwrap();
+    let storage = S3Storage::new(
+        &std::env::var("S3_TEST_BUCKET").unwrap_or_else(|_| String::from("emp-logger")),
+        "us-east-1",
+        &endpoint,
+        &std::env::var("S3_TEST_ACCESS_KEY").unwrap_or_else(|_| String::from("minioadmin")),
+        &std::env::var("S3_TEST_SECRET_KEY").unwrap_or_else(|_| String::from("minioadmin")),
+        format!("{}/emp-logger", endpoint),
+    )
+    .unwrap();
+
+    storage
+        .put(
+            "avatars/1/00ff/64.png",
+            "image/png",
+            Bytes::from_static(b"png"),
+        )
+        .await
+        .unwrap();
+    storage.delete("avatars/1/00ff/64.png").await.unwrap();
+}
@@ -0,0 +1,37 @@
+use emp_logger::validation::{rules, ValidationErrors};
+
+#[test]
+fn username_rules() {
+    assert!(rules::username("jane.doe_01").is_ok());
+    assert!(rules::username("ab").is_err());
+    assert!(rules::username("has space").is_err());
+}
+
+#[test]
+fn password_requires_letters_and_digits() {
+    assert!(rules::password("harvest2024").is_ok());
+    assert!(rules::password("short1").is_err());
+    assert!(rules::password("onlyletters").is_err());
+}
+
+#[test]
+fn image_url_must_be_http() {
+    assert!(rules::image_url("https://cdn.example.com/a.png").is_ok());
+    assert!(rules::image_url("javascript:alert(1)").is_err());
+}
+
+#[test]
+fn collects_every_field_error() {
+    let mut errors = ValidationErrors::default();
+    errors.check("username", rules::username("x"));
+    errors.check("password", rules::password("x"));
+    errors.check("bio", rules::bio("fine"));
+
+    match errors.into_result() {
+        Err(emp_logger::app_error::AppError::Validation(fields)) => {
+            let names: Vec<_> = fields.iter().map(|f| f.field.as_str()).collect();
+            assert_eq!(names, vec!["username", "password"]);
+        }
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
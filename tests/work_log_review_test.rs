@@ -0,0 +1,44 @@
+use emp_logger::{
+    domain::work_logs::review::{amend, review, Decision},
+    prisma::WorkLogStatus,
+};
+
+#[test]
+fn submitted_logs_can_be_approved_or_rejected() {
+    assert_eq!(
+        review(WorkLogStatus::Submitted, Decision::Approve),
+        Ok(WorkLogStatus::Approved)
+    );
+    assert_eq!(
+        review(WorkLogStatus::Submitted, Decision::Reject),
+        Ok(WorkLogStatus::Rejected)
+    );
+}
+
+#[test]
+fn amended_logs_go_back_to_review() {
+    assert_eq!(amend(WorkLogStatus::Rejected), Ok(WorkLogStatus::Amended));
+    assert_eq!(
+        review(WorkLogStatus::Amended, Decision::Approve),
+        Ok(WorkLogStatus::Approved)
+    );
+}
+
+#[test]
+fn rejected_logs_must_be_amended_before_review() {
+    assert!(review(WorkLogStatus::Rejected, Decision::Approve).is_err());
+}
+
+#[test]
+fn approved_logs_are_final() {
+    assert!(review(WorkLogStatus::Approved, Decision::Reject).is_err());
+    assert!(amend(WorkLogStatus::Approved).is_err());
+}
+
+#[test]
+fn editing_a_submitted_log_keeps_it_submitted() {
+    assert_eq!(
+        amend(WorkLogStatus::Submitted),
+        Ok(WorkLogStatus::Submitted)
+    );
+}